@@ -0,0 +1,73 @@
+// Compares the single-pass scanner against the original per-word search on generated input.
+// Run with `cargo bench --bench day01`.
+use aoc2023::day01::{
+    extract_calibration_value, extract_calibration_value_naive, CalibrationError, CalibrationMode,
+};
+use std::hint::black_box;
+use std::time::Instant;
+
+const WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+// A linear congruential generator, so the input is the same on every run.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+// Lines of letters, digits and number words. Every line gets at least one digit.
+fn generate(count: usize) -> Vec<String> {
+    let mut rng = Lcg(2023);
+    (0..count)
+        .map(|_| {
+            let mut line = String::new();
+            for _ in 0..(8 + rng.next(24)) {
+                match rng.next(10) {
+                    0 => line.push((b'1' + rng.next(9) as u8) as char),
+                    1 => line += WORDS[rng.next(9) as usize],
+                    _ => line.push((b'a' + rng.next(26) as u8) as char),
+                }
+            }
+            let at = rng.next(line.len() as u64 + 1) as usize;
+            line.insert(at, (b'1' + rng.next(9) as u8) as char);
+            line
+        })
+        .collect()
+}
+
+fn bench(name: &str, lines: &[String], extract: fn(&str) -> Result<i64, CalibrationError>) -> i64 {
+    let rounds = 10;
+    let start = Instant::now();
+    let mut sum = 0;
+    for _ in 0..rounds {
+        sum = lines
+            .iter()
+            .map(|line| extract(black_box(line)).unwrap())
+            .sum();
+    }
+    let elapsed = start.elapsed() / rounds;
+    println!(
+        "{:>8}: {:.2?} per round, {:.0} lines/s",
+        name,
+        elapsed,
+        lines.len() as f64 / elapsed.as_secs_f64()
+    );
+    sum
+}
+
+fn main() {
+    let lines = generate(100_000);
+    let naive = bench("naive", &lines, extract_calibration_value_naive);
+    let scanner = bench("scanner", &lines, |line| {
+        extract_calibration_value(line, CalibrationMode::DigitsAndWords)
+    });
+    assert_eq!(naive, scanner);
+}
@@ -1,4 +1,13 @@
+use aoc_core::config::OutputFormat;
+use aoc_core::format::{escape_csv, text_table};
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution, View};
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
 use std::string::String;
+use std::sync::OnceLock;
 
 const NUMBER_WORDS: [&str; 9] = [
     "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
@@ -6,86 +15,743 @@ const NUMBER_WORDS: [&str; 9] = [
 
 const NUMBER_NUMBERS: [i64; 9] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
 
-fn find_number_words(line: &str) -> (Option<usize>, Option<usize>) {
-    let mut least_word_pos = None;
-    let mut least_word_idx = None;
-    for (i, word) in NUMBER_WORDS.iter().enumerate() {
-        if let Some(word_pos) = line.find(word) {
-            if word_pos < least_word_pos.unwrap_or(line.len() - 1) {
-                least_word_pos = Some(word_pos);
-                least_word_idx = Some(i);
+// The position and value of the first number word in the line.
+fn find_number_words(line: &str) -> Option<(usize, i64)> {
+    NUMBER_WORDS
+        .iter()
+        .zip(NUMBER_NUMBERS)
+        .filter_map(|(word, value)| line.find(word).map(|pos| (pos, value)))
+        .min()
+}
+
+// The position and value of the last number word in the line.
+fn rfind_number_words(line: &str) -> Option<(usize, i64)> {
+    NUMBER_WORDS
+        .iter()
+        .zip(NUMBER_NUMBERS)
+        .filter_map(|(word, value)| line.rfind(word).map(|pos| (pos, value)))
+        .max()
+}
+
+fn digit_at(line: &str, pos: usize) -> (usize, i64) {
+    (pos, (line.as_bytes()[pos] - b'0') as i64)
+}
+
+fn naive_first_digit(line: &str) -> Option<(usize, i64)> {
+    let number = line
+        .find(|c: char| c.is_ascii_digit())
+        .map(|pos| digit_at(line, pos));
+    // A digit and a word can't start at the same position, so comparing positions is enough.
+    match (number, find_number_words(line)) {
+        (Some(number), Some(word)) => Some(number.min(word)),
+        (number, word) => number.or(word),
+    }
+}
+
+fn naive_last_digit(line: &str) -> Option<(usize, i64)> {
+    let number = line
+        .rfind(|c: char| c.is_ascii_digit())
+        .map(|pos| digit_at(line, pos));
+    match (number, rfind_number_words(line)) {
+        (Some(number), Some(word)) => Some(number.max(word)),
+        (number, word) => number.or(word),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CalibrationError {
+    EmptyLine,
+    // The line has no digit, or no number word either when words count.
+    NoDigit,
+    // Only when streaming, since a &str is always valid.
+    InvalidUtf8,
+    // A custom vocabulary's values are too large to combine into an i64.
+    Overflow,
+}
+
+impl fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationError::EmptyLine => write!(f, "empty line"),
+            CalibrationError::NoDigit => write!(f, "no digit"),
+            CalibrationError::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            CalibrationError::Overflow => write!(f, "value too large"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LineError {
+    // Numbered from 1.
+    pub line: usize,
+    pub error: CalibrationError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+// What was read from a stream. Lines with errors aren't part of the sum.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct StreamTotal {
+    pub sum: i64,
+    pub lines: usize,
+    pub errors: usize,
+}
+
+fn combine(
+    line: &str,
+    first: Option<(usize, i64)>,
+    last: Option<(usize, i64)>,
+) -> Result<i64, CalibrationError> {
+    if line.is_empty() {
+        return Err(CalibrationError::EmptyLine);
+    }
+    match (first, last) {
+        (Some((_, first)), Some((_, last))) => first
+            .checked_mul(10)
+            .and_then(|tens| tens.checked_add(last))
+            .ok_or(CalibrationError::Overflow),
+        _ => Err(CalibrationError::NoDigit),
+    }
+}
+
+// The original implementation, which searches each line once per word. Kept as the baseline for
+// benches/day01.rs.
+pub fn extract_calibration_value_naive(line: &str) -> Result<i64, CalibrationError> {
+    combine(line, naive_first_digit(line), naive_last_digit(line))
+}
+
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: Vec<(u8, usize)>,
+    value: Option<i64>,
+}
+
+// A byte trie over the number words.
+#[derive(Debug)]
+struct Trie {
+    nodes: Vec<TrieNode>,
+}
+
+impl Trie {
+    fn new() -> Trie {
+        Trie {
+            nodes: vec![TrieNode::default()],
+        }
+    }
+
+    fn insert(&mut self, word: impl Iterator<Item = u8>, value: i64) {
+        let mut node = 0;
+        for b in word {
+            node = match self.child(node, b) {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.push((b, child));
+                    child
+                }
+            };
+        }
+        self.nodes[node].value = Some(value);
+    }
+
+    fn child(&self, node: usize, b: u8) -> Option<usize> {
+        self.nodes[node]
+            .children
+            .iter()
+            .find(|(child_b, _)| *child_b == b)
+            .map(|(_, child)| *child)
+    }
+
+    // Walks the trie along bytes, returning the length and value of the longest word they start
+    // with.
+    fn longest_match(&self, bytes: impl Iterator<Item = u8>) -> Option<(usize, i64)> {
+        let mut node = 0;
+        let mut result = None;
+        for (i, b) in bytes.enumerate() {
+            match self.child(node, b) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some(value) = self.nodes[node].value {
+                result = Some((i + 1, value));
             }
         }
+        result
+    }
+}
+
+// The zero of every run of Unicode decimal digits (category Nd) as of Unicode 17. Each run holds
+// the digits 0-9 in order. Digits added in later versions aren't recognised.
+const DECIMAL_ZEROS: [u32; 77] = [
+    0x0030, 0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80,
+    0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10, 0x104A0, 0x10D30, 0x10D40, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450,
+    0x114D0, 0x11650, 0x116C0, 0x116D0, 0x116DA, 0x11730, 0x118E0, 0x11950, 0x11BF0, 0x11C50,
+    0x11D50, 0x11DA0, 0x11DE0, 0x11F50, 0x16130, 0x16A60, 0x16AC0, 0x16B50, 0x16D70, 0x1CCF0,
+    0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E4F0, 0x1E5F1, 0x1E950,
+    0x1FBF0,
+];
+
+fn unicode_decimal_value(c: char) -> Option<i64> {
+    let c = c as u32;
+    let i = DECIMAL_ZEROS.partition_point(|&zero| zero <= c);
+    let offset = c - DECIMAL_ZEROS[i.checked_sub(1)?];
+    if offset < 10 {
+        Some(offset as i64)
+    } else {
+        None
+    }
+}
+
+// Which characters count as digits. Other numerals, like '½' or '③', never do.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DigitPolicy {
+    // Only '0' through '9'.
+    #[default]
+    Ascii,
+    // Any decimal digit, like '٣' (Arabic-Indic three) or '７' (fullwidth seven).
+    UnicodeDecimal,
+}
+
+impl DigitPolicy {
+    pub fn parse(s: &str) -> Result<DigitPolicy, String> {
+        match s {
+            "ascii" => Ok(DigitPolicy::Ascii),
+            "unicode" => Ok(DigitPolicy::UnicodeDecimal),
+            _ => Err(format!("Invalid digit policy {}", s)),
+        }
+    }
+
+    pub fn value(self, c: char) -> Option<i64> {
+        match self {
+            DigitPolicy::Ascii => c.to_digit(10).map(|digit| digit as i64),
+            DigitPolicy::UnicodeDecimal => unicode_decimal_value(c),
+        }
     }
-    (least_word_idx, least_word_pos)
 }
 
-fn rfind_number_words(line: &str) -> (Option<usize>, Option<usize>) {
-    let mut greatest_word_pos = None;
-    let mut greatest_word_idx = None;
-    for (i, word) in NUMBER_WORDS.iter().enumerate() {
-        if let Some(word_pos) = line.rfind(word) {
-            if word_pos > greatest_word_pos.unwrap_or(0) {
-                greatest_word_pos = Some(word_pos);
-                greatest_word_idx = Some(i)
+// The words that count as digits, besides the digits themselves.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Vocabulary {
+    words: Vec<(String, i64)>,
+    // Matches words regardless of ASCII case.
+    ignore_case: bool,
+    digits: DigitPolicy,
+}
+
+impl Vocabulary {
+    // No words, so only digits count.
+    pub fn new() -> Vocabulary {
+        Vocabulary::default()
+    }
+
+    // "one" through "nine", as in the puzzle.
+    pub fn english() -> Vocabulary {
+        NUMBER_WORDS
+            .iter()
+            .zip(NUMBER_NUMBERS)
+            .fold(Vocabulary::new(), |vocabulary, (word, value)| {
+                vocabulary.word(word, value)
+            })
+    }
+
+    // Adds a word, replacing its value if it's already there.
+    pub fn word(mut self, word: &str, value: i64) -> Vocabulary {
+        self.words.retain(|(existing, _)| existing != word);
+        self.words.push((String::from(word), value));
+        self
+    }
+
+    pub fn ignore_case(mut self, ignore_case: bool) -> Vocabulary {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    pub fn digits(mut self, digits: DigitPolicy) -> Vocabulary {
+        self.digits = digits;
+        self
+    }
+
+    pub fn words(&self) -> &[(String, i64)] {
+        &self.words
+    }
+
+    // Parses one `word = value` pair per line. Blank lines and lines starting with # are skipped,
+    // `ignore_case = true` turns on case-insensitive matching and `digits = unicode` accepts any
+    // decimal digit.
+    pub fn parse(text: &str) -> Result<Vocabulary, String> {
+        let mut vocabulary = Vocabulary::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |err: String| format!("vocabulary:{}: {}", i + 1, err);
+            let (word, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(String::from("Expected word = value")))?;
+            let (word, value) = (word.trim(), value.trim());
+            if word == "ignore_case" {
+                vocabulary.ignore_case = match value {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(error(format!("Invalid ignore_case {}", value))),
+                };
+                continue;
             }
+            if word == "digits" {
+                vocabulary.digits = DigitPolicy::parse(value).map_err(error)?;
+                continue;
+            }
+            if word.is_empty() || word.bytes().any(|b| b.is_ascii_digit()) {
+                return Err(error(format!("Invalid word {:?}", word)));
+            }
+            let value = value
+                .parse::<i64>()
+                .map_err(|_| error(format!("Invalid value {}", value)))?;
+            vocabulary = vocabulary.word(word, value);
         }
+        Ok(vocabulary)
+    }
+
+    pub fn load(path: &Path) -> Result<Vocabulary, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+        Vocabulary::parse(&text)
     }
-    (greatest_word_idx, greatest_word_pos)
 }
 
-fn get_pos_or_max(maybe_pos: Option<usize>, line: &String) -> usize {
-    maybe_pos.unwrap_or(line.len() - 1)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CalibrationMode {
+    // Part 1: only digits count. '0' is a digit too, under either DigitPolicy.
+    DigitsOnly,
+    // Part 2: spelled-out words like "one" count too.
+    DigitsAndWords,
 }
 
-fn get_pos_or_min(maybe_pos: Option<usize>) -> usize {
-    maybe_pos.unwrap_or(0)
+impl CalibrationMode {
+    pub fn for_part(part: u8) -> CalibrationMode {
+        match part {
+            1 => CalibrationMode::DigitsOnly,
+            _ => CalibrationMode::DigitsAndWords,
+        }
+    }
+
+    pub fn vocabulary(self) -> Vocabulary {
+        match self {
+            CalibrationMode::DigitsOnly => Vocabulary::new(),
+            CalibrationMode::DigitsAndWords => Vocabulary::english(),
+        }
+    }
 }
 
-pub fn extract_calibration_value(line: &String) -> i64 {
-    let first_number_pos = line.find(char::is_numeric);
-    let (first_word_idx, first_word_pos) = find_number_words(line);
-    let first_num = if get_pos_or_max(first_word_pos, line) < get_pos_or_max(first_number_pos, line)
-    {
-        *NUMBER_NUMBERS.get(first_word_idx.unwrap()).unwrap()
-    } else {
-        let first_pos = first_number_pos.unwrap();
-        line.get(first_pos..(first_pos + 1))
-            .unwrap()
-            .parse::<i64>()
-            .unwrap()
-    };
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TokenKind {
+    Digit,
+    Word,
+}
 
-    let second_number_pos = line.rfind(char::is_numeric);
-    let (second_word_idx, second_word_pos) = rfind_number_words(line);
-    let second_num = if get_pos_or_min(second_word_pos) > get_pos_or_min(second_number_pos) {
-        *NUMBER_NUMBERS.get(second_word_idx.unwrap()).unwrap()
-    } else {
-        let second_pos = second_number_pos.unwrap();
-        line.get(second_pos..(second_pos + 1))
-            .unwrap()
-            .parse::<i64>()
-            .unwrap()
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Digit => write!(f, "digit"),
+            TokenKind::Word => write!(f, "word"),
+        }
+    }
+}
+
+// A digit or number word found in a line, spanning bytes start..end.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Token {
+    pub start: usize,
+    pub end: usize,
+    pub kind: TokenKind,
+    pub value: i64,
+}
+
+// Finds the first and last digit or number word in a line in one pass from each end. Words may
+// overlap, so "eightwo" starts with 8 and ends with 2.
+#[derive(Debug)]
+pub struct Scanner {
+    forward: Trie,
+    // The words reversed, for matching words that end at a position.
+    backward: Trie,
+    ignore_case: bool,
+    digits: DigitPolicy,
+}
+
+impl Scanner {
+    pub fn new(vocabulary: &Vocabulary) -> Scanner {
+        let mut scanner = Scanner {
+            forward: Trie::new(),
+            backward: Trie::new(),
+            ignore_case: vocabulary.ignore_case,
+            digits: vocabulary.digits,
+        };
+        for (word, value) in vocabulary.words() {
+            let word = scanner.fold_case(word.as_bytes());
+            scanner.forward.insert(word.iter().copied(), *value);
+            scanner.backward.insert(word.iter().rev().copied(), *value);
+        }
+        scanner
+    }
+
+    fn fold_case(&self, bytes: &[u8]) -> Vec<u8> {
+        if self.ignore_case {
+            bytes.to_ascii_lowercase()
+        } else {
+            bytes.to_vec()
+        }
+    }
+
+    fn fold_byte(&self, b: u8) -> u8 {
+        if self.ignore_case {
+            b.to_ascii_lowercase()
+        } else {
+            b
+        }
+    }
+
+    // The digit starting at byte i. Positions inside a multi-byte character never hold one.
+    fn digit_token(&self, line: &str, i: usize) -> Option<Token> {
+        if !line.is_char_boundary(i) {
+            return None;
+        }
+        let c = line[i..].chars().next()?;
+        self.digits.value(c).map(|value| Token {
+            start: i,
+            end: i + c.len_utf8(),
+            kind: TokenKind::Digit,
+            value,
+        })
+    }
+
+    pub fn first_token(&self, line: &str) -> Option<Token> {
+        let bytes = line.as_bytes();
+        (0..bytes.len()).find_map(|i| {
+            self.digit_token(line, i).or_else(|| {
+                self.forward
+                    .longest_match(bytes[i..].iter().map(|&b| self.fold_byte(b)))
+                    .map(|(len, value)| Token {
+                        start: i,
+                        end: i + len,
+                        kind: TokenKind::Word,
+                        value,
+                    })
+            })
+        })
+    }
+
+    pub fn last_token(&self, line: &str) -> Option<Token> {
+        let bytes = line.as_bytes();
+        (0..bytes.len()).rev().find_map(|i| {
+            self.digit_token(line, i).or_else(|| {
+                self.backward
+                    .longest_match(bytes[..=i].iter().rev().map(|&b| self.fold_byte(b)))
+                    .map(|(len, value)| Token {
+                        start: i + 1 - len,
+                        end: i + 1,
+                        kind: TokenKind::Word,
+                        value,
+                    })
+            })
+        })
+    }
+
+    // The byte position and value of the first digit or number word.
+    pub fn first(&self, line: &str) -> Option<(usize, i64)> {
+        self.first_token(line)
+            .map(|token| (token.start, token.value))
+    }
+
+    // The byte position and value of the last digit or number word.
+    pub fn last(&self, line: &str) -> Option<(usize, i64)> {
+        self.last_token(line)
+            .map(|token| (token.start, token.value))
+    }
+
+    pub fn calibration_value(&self, line: &str) -> Result<i64, CalibrationError> {
+        combine(line, self.first(line), self.last(line))
+    }
+
+    // Sums the calibration values of every line in reader, reusing one buffer so memory doesn't
+    // grow with the input. Bad lines are passed to on_error and skipped; only I/O errors stop the
+    // stream.
+    pub fn sum_reader(
+        &self,
+        mut reader: impl BufRead,
+        mut on_error: impl FnMut(LineError),
+    ) -> io::Result<StreamTotal> {
+        let mut total = StreamTotal::default();
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                return Ok(total);
+            }
+            total.lines += 1;
+            let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let value = std::str::from_utf8(line)
+                .map_err(|_| CalibrationError::InvalidUtf8)
+                .and_then(|line| self.calibration_value(line));
+            match value {
+                Ok(value) => total.sum += value,
+                Err(error) => {
+                    total.errors += 1;
+                    on_error(LineError {
+                        line: total.lines,
+                        error,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn scanner(mode: CalibrationMode) -> &'static Scanner {
+    static DIGITS: OnceLock<Scanner> = OnceLock::new();
+    static WORDS: OnceLock<Scanner> = OnceLock::new();
+    let scanner = match mode {
+        CalibrationMode::DigitsOnly => &DIGITS,
+        CalibrationMode::DigitsAndWords => &WORDS,
     };
+    scanner.get_or_init(|| Scanner::new(&mode.vocabulary()))
+}
+
+pub fn extract_calibration_value(
+    line: &str,
+    mode: CalibrationMode,
+) -> Result<i64, CalibrationError> {
+    scanner(mode).calibration_value(line)
+}
+
+// Fails on the first line without a calibration value.
+pub fn sum_calibration_values<S: AsRef<str>>(
+    lines: &[S],
+    mode: CalibrationMode,
+) -> Result<i64, LineError> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            extract_calibration_value(line.as_ref(), mode)
+                .map_err(|error| LineError { line: i + 1, error })
+        })
+        .sum()
+}
+
+pub fn sum_calibration_reader(
+    reader: impl BufRead,
+    mode: CalibrationMode,
+    on_error: impl FnMut(LineError),
+) -> io::Result<StreamTotal> {
+    scanner(mode).sum_reader(reader, on_error)
+}
 
-    return first_num * 10 + second_num;
+// Which tokens were picked on a line, for working out why a sum is off.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LineMatch {
+    // Numbered from 1.
+    pub line: usize,
+    pub text: String,
+    pub first: Option<Token>,
+    pub last: Option<Token>,
+    pub value: Result<i64, CalibrationError>,
 }
 
-fn sum_calibration_values(lines: &[String]) -> i64 {
+pub fn match_report<S: AsRef<str>>(lines: &[S], scanner: &Scanner) -> Vec<LineMatch> {
     lines
         .iter()
-        .map(extract_calibration_value)
-        .fold(0, |acc, x| acc + x)
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.as_ref();
+            LineMatch {
+                line: i + 1,
+                text: String::from(line),
+                first: scanner.first_token(line),
+                last: scanner.last_token(line),
+                value: scanner.calibration_value(line),
+            }
+        })
+        .collect()
+}
+
+// The token's text, byte position and kind as cells, or blanks if there's no token.
+fn token_cells(text: &str, token: Option<Token>) -> [String; 3] {
+    match token {
+        Some(token) => [
+            String::from(&text[token.start..token.end]),
+            token.start.to_string(),
+            token.kind.to_string(),
+        ],
+        None => [String::new(), String::new(), String::new()],
+    }
+}
+
+fn value_cell(value: &Result<i64, CalibrationError>) -> String {
+    match value {
+        Ok(value) => value.to_string(),
+        Err(err) => format!("error: {}", err),
+    }
+}
+
+const MATCH_HEADER: [&str; 8] = [
+    "line",
+    "first",
+    "first_pos",
+    "first_kind",
+    "last",
+    "last_pos",
+    "last_kind",
+    "value",
+];
+
+fn match_cells(m: &LineMatch) -> Vec<String> {
+    let mut cells = vec![m.line.to_string()];
+    cells.extend(token_cells(&m.text, m.first));
+    cells.extend(token_cells(&m.text, m.last));
+    cells.push(value_cell(&m.value));
+    cells
+}
+
+pub fn matches_csv(matches: &[LineMatch]) -> String {
+    let mut out = MATCH_HEADER.join(",") + "\n";
+    for m in matches {
+        let cells: Vec<String> = match_cells(m).iter().map(|cell| escape_csv(cell)).collect();
+        out += &(cells.join(",") + "\n");
+    }
+    out
+}
+
+// The report as a table with aligned columns.
+pub fn matches_table(matches: &[LineMatch]) -> String {
+    let rows: Vec<Vec<String>> = matches.iter().map(match_cells).collect();
+    text_table(&MATCH_HEADER, &rows)
+}
+
+fn explain_calibration_values(lines: &[String], mode: CalibrationMode, trace: &mut Trace) {
+    for (i, line) in lines.iter().enumerate() {
+        if !trace.wants(i) {
+            continue;
+        }
+        trace.emit(i, "line", line.clone());
+        let scanner = scanner(mode);
+        if let Some((_, first)) = scanner.first(line) {
+            trace.emit(i, "first", first.to_string());
+        }
+        if let Some((_, last)) = scanner.last(line) {
+            trace.emit(i, "last", last.to_string());
+        }
+        match extract_calibration_value(line, mode) {
+            Ok(value) => trace.emit(i, "value", value.to_string()),
+            Err(err) => trace.emit(i, "error", err.to_string()),
+        }
+    }
+}
+
+const EXAMPLE_1: &str = "\
+1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet
+";
+
+const EXAMPLE_2: &str = "\
+two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen
+";
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    fn day(&self) -> &'static str {
+        "day01"
+    }
+
+    fn try_part1(&self, lines: &[String]) -> Option<Result<i64, String>> {
+        Some(
+            sum_calibration_values(lines, CalibrationMode::DigitsOnly)
+                .map_err(|err| err.to_string()),
+        )
+    }
+
+    fn try_part2(&self, lines: &[String]) -> Option<Result<i64, String>> {
+        Some(
+            sum_calibration_values(lines, CalibrationMode::DigitsAndWords)
+                .map_err(|err| err.to_string()),
+        )
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        explain_calibration_values(lines, CalibrationMode::for_part(part), trace);
+    }
+
+    // `matches` shows the tokens picked on each line, for part 2 unless --part 1 is given.
+    // `--set digits=unicode` accepts any decimal digit.
+    fn view(&self, view: &View, lines: &[String]) -> Option<Result<String, String>> {
+        if view.name != "matches" {
+            return None;
+        }
+        let digits = match view.param("digits").map(DigitPolicy::parse) {
+            Some(Ok(digits)) => digits,
+            Some(Err(err)) => return Some(Err(err)),
+            None => DigitPolicy::Ascii,
+        };
+        let mode = CalibrationMode::for_part(view.part.unwrap_or(2));
+        let scanner = Scanner::new(&mode.vocabulary().digits(digits));
+        let matches = match_report(lines, &scanner);
+        Some(match view.format {
+            OutputFormat::Text => Ok(matches_table(&matches)),
+            OutputFormat::Csv => Ok(matches_csv(&matches)),
+            OutputFormat::Json => Err(String::from("The matches view has no JSON output")),
+        })
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            1 => Some(Example {
+                input: EXAMPLE_1,
+                answer: 142,
+            }),
+            2 => Some(Example {
+                input: EXAMPLE_2,
+                answer: 281,
+            }),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day01::sum_calibration_values;
+    use crate::day01::{
+        explain_calibration_values, extract_calibration_value, extract_calibration_value_naive,
+        match_report, matches_csv, matches_table, scanner, sum_calibration_reader,
+        sum_calibration_values, CalibrationError, CalibrationMode, Day01, DigitPolicy, LineError,
+        Scanner, StreamTotal, Token, TokenKind, Vocabulary, DECIMAL_ZEROS,
+    };
+    use aoc_core::trace::Trace;
+    use aoc_core::Solution;
 
     #[test]
     fn sum_calibration_values_basic() {
         let inputs = vec![String::from("1abc2")];
-        assert_eq!(sum_calibration_values(&inputs), 12);
+        assert_eq!(
+            sum_calibration_values(&inputs, CalibrationMode::DigitsOnly),
+            Ok(12)
+        );
     }
 
     #[test]
@@ -99,7 +765,10 @@ mod tests {
             String::from("zoneight234"),
             String::from("7pqrstsixteen"),
         ];
-        assert_eq!(sum_calibration_values(&inputs), 281);
+        assert_eq!(
+            sum_calibration_values(&inputs, CalibrationMode::DigitsAndWords),
+            Ok(281)
+        );
     }
 
     #[test]
@@ -110,6 +779,325 @@ mod tests {
             String::from("a1b2c3d4e5f"),
             String::from("treb7uchet"),
         ];
-        assert_eq!(sum_calibration_values(&inputs), 142);
+        assert_eq!(
+            sum_calibration_values(&inputs, CalibrationMode::DigitsOnly),
+            Ok(142)
+        );
+        assert_eq!(
+            sum_calibration_values(&inputs, CalibrationMode::DigitsAndWords),
+            Ok(142)
+        );
+    }
+
+    #[test]
+    fn digits_only_ignores_words() {
+        assert_eq!(
+            extract_calibration_value("two1nine", CalibrationMode::DigitsOnly),
+            Ok(11)
+        );
+        assert_eq!(
+            extract_calibration_value("zoneight234", CalibrationMode::DigitsOnly),
+            Ok(24)
+        );
+        assert_eq!(
+            extract_calibration_value("zoneight234", CalibrationMode::DigitsAndWords),
+            Ok(14)
+        );
+    }
+
+    #[test]
+    fn explain_calibration_values_success() {
+        let inputs = vec![String::from("two1nine"), String::from("xtwone3four")];
+        let mut trace = Trace::new(Some(1));
+        explain_calibration_values(&inputs, CalibrationMode::DigitsAndWords, &mut trace);
+        let details: Vec<&str> = trace
+            .events()
+            .iter()
+            .map(|event| event.detail.as_str())
+            .collect();
+        assert_eq!(details, vec!["xtwone3four", "2", "4", "24"]);
+    }
+
+    #[test]
+    fn extract_calibration_value_overlapping_words() {
+        const WORDS: CalibrationMode = CalibrationMode::DigitsAndWords;
+        assert_eq!(extract_calibration_value("eightwo", WORDS), Ok(82));
+        assert_eq!(extract_calibration_value("twone", WORDS), Ok(21));
+        assert_eq!(extract_calibration_value("oneight", WORDS), Ok(18));
+        assert_eq!(extract_calibration_value("sevenine", WORDS), Ok(79));
+        assert_eq!(extract_calibration_value("xeightwothreex", WORDS), Ok(83));
+    }
+
+    #[test]
+    fn scanner_positions() {
+        let scanner = Scanner::new(&Vocabulary::english());
+        assert_eq!(scanner.first("zoneight234"), Some((1, 1)));
+        assert_eq!(scanner.last("zoneight234"), Some((10, 4)));
+        assert_eq!(scanner.last("zoneight"), Some((3, 8)));
+        assert_eq!(scanner.first("abc"), None);
+        // '٣' takes two bytes, and its position is where it starts.
+        let scanner = Scanner::new(&Vocabulary::new().digits(DigitPolicy::UnicodeDecimal));
+        let line = "a٣b";
+        assert_eq!(scanner.last(line), Some((1, 3)));
+        assert!(line.is_char_boundary(1));
+    }
+
+    #[test]
+    fn scanner_matches_naive() {
+        let lines = [
+            "two1nine",
+            "abcone2threexyz",
+            "xtwone3four",
+            "4nineeightseven2",
+            "zoneight234",
+            "7pqrstsixteen",
+            "pqr3stu8vwx",
+        ];
+        for line in lines {
+            assert_eq!(
+                extract_calibration_value(line, CalibrationMode::DigitsAndWords),
+                extract_calibration_value_naive(line),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn extract_calibration_value_edge_cases() {
+        fn check(extract: fn(&str) -> Result<i64, CalibrationError>) {
+            assert_eq!(extract(""), Err(CalibrationError::EmptyLine));
+            assert_eq!(extract("x"), Err(CalibrationError::NoDigit));
+            assert_eq!(extract("7"), Ok(77));
+            // Words at the very start and end of the line.
+            assert_eq!(extract("one2"), Ok(12));
+            assert_eq!(extract("2one"), Ok(21));
+            assert_eq!(extract("nine"), Ok(99));
+            assert_eq!(extract("onex5xnine"), Ok(19));
+        }
+        check(|line| extract_calibration_value(line, CalibrationMode::DigitsAndWords));
+        check(extract_calibration_value_naive);
+        assert_eq!(
+            extract_calibration_value("one", CalibrationMode::DigitsOnly),
+            Err(CalibrationError::NoDigit)
+        );
+    }
+
+    #[test]
+    fn sum_calibration_values_names_bad_line() {
+        let inputs = vec![String::from("1abc2"), String::from("abc")];
+        assert_eq!(
+            sum_calibration_values(&inputs, CalibrationMode::DigitsOnly),
+            Err(LineError {
+                line: 2,
+                error: CalibrationError::NoDigit
+            })
+        );
+    }
+
+    #[test]
+    fn vocabulary_parse_success() {
+        let text = "
+# Dutch, plus a zero
+ignore_case = true
+een = 1
+twee = 2
+drie = 3
+nul = 0
+";
+        assert_eq!(
+            Vocabulary::parse(text),
+            Ok(Vocabulary::new()
+                .word("een", 1)
+                .word("twee", 2)
+                .word("drie", 3)
+                .word("nul", 0)
+                .ignore_case(true))
+        );
+    }
+
+    #[test]
+    fn vocabulary_parse_errors() {
+        assert_eq!(
+            Vocabulary::parse("een = 1\ntwee"),
+            Err(String::from("vocabulary:2: Expected word = value"))
+        );
+        assert!(Vocabulary::parse("een = one").is_err());
+        assert!(Vocabulary::parse(" = 1").is_err());
+        assert!(Vocabulary::parse("4 = 4").is_err());
+        assert!(Vocabulary::parse("ignore_case = maybe").is_err());
+    }
+
+    #[test]
+    fn scanner_custom_vocabulary() {
+        let vocabulary = Vocabulary::english()
+            .word("zero", 0)
+            .word("dozen", 12)
+            .ignore_case(true);
+        let scanner = Scanner::new(&vocabulary);
+        assert_eq!(scanner.calibration_value("ZeroXnine"), Ok(9));
+        assert_eq!(scanner.calibration_value("Two dozen"), Ok(32));
+        assert_eq!(scanner.calibration_value("SEVENINE"), Ok(79));
+        let scanner = Scanner::new(&Vocabulary::english());
+        assert_eq!(
+            scanner.calibration_value("SEVEN"),
+            Err(CalibrationError::NoDigit)
+        );
+    }
+
+    #[test]
+    fn scanner_value_overflow() {
+        let scanner = Scanner::new(&Vocabulary::new().word("huge", i64::MAX));
+        assert_eq!(
+            scanner.calibration_value("huge"),
+            Err(CalibrationError::Overflow)
+        );
+        assert_eq!(CalibrationError::Overflow.to_string(), "value too large");
+    }
+
+    #[test]
+    fn sum_calibration_values_strs() {
+        let inputs = ["1abc2", "pqr3stu8vwx"];
+        assert_eq!(
+            sum_calibration_values(&inputs, CalibrationMode::DigitsOnly),
+            Ok(50)
+        );
+    }
+
+    #[test]
+    fn sum_calibration_reader_success() {
+        let input: &[u8] = b"two1nine\r\n\nabc\n\xffone\nzoneight234\n7pqrstsixteen";
+        let mut errors = Vec::new();
+        let total = sum_calibration_reader(input, CalibrationMode::DigitsAndWords, |err| {
+            errors.push(err.to_string())
+        });
+        assert_eq!(
+            total.unwrap(),
+            StreamTotal {
+                sum: 29 + 14 + 76,
+                lines: 6,
+                errors: 3,
+            }
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "line 2: empty line",
+                "line 3: no digit",
+                "line 4: invalid UTF-8"
+            ]
+        );
+    }
+
+    #[test]
+    fn match_report_success() {
+        let matches = match_report(
+            &["xtwone3four", "abc"],
+            scanner(CalibrationMode::DigitsAndWords),
+        );
+        assert_eq!(
+            matches[0].first,
+            Some(Token {
+                start: 1,
+                end: 4,
+                kind: TokenKind::Word,
+                value: 2,
+            })
+        );
+        assert_eq!(
+            matches[0].last,
+            Some(Token {
+                start: 7,
+                end: 11,
+                kind: TokenKind::Word,
+                value: 4,
+            })
+        );
+        assert_eq!(matches[0].value, Ok(24));
+        assert_eq!(matches[1].first, None);
+        assert_eq!(matches[1].value, Err(CalibrationError::NoDigit));
+    }
+
+    #[test]
+    fn matches_csv_success() {
+        let matches = match_report(
+            &["7pqrstsixteen", "a,b"],
+            scanner(CalibrationMode::DigitsAndWords),
+        );
+        assert_eq!(
+            matches_csv(&matches),
+            "line,first,first_pos,first_kind,last,last_pos,last_kind,value
+1,7,0,digit,six,6,word,76
+2,,,,,,,error: no digit
+"
+        );
+    }
+
+    #[test]
+    fn matches_table_success() {
+        let matches = match_report(&["two1nine"], scanner(CalibrationMode::DigitsOnly));
+        assert_eq!(
+            matches_table(&matches),
+            "line  first  first_pos  first_kind  last  last_pos  last_kind  value
+1     1      3          digit       1     3         digit      11
+"
+        );
+    }
+
+    #[test]
+    fn digit_policy_value() {
+        assert_eq!(DigitPolicy::Ascii.value('7'), Some(7));
+        assert_eq!(DigitPolicy::Ascii.value('\u{663}'), None);
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{663}'), Some(3));
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{ff17}'), Some(7));
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{1d7ff}'), Some(9));
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{bd}'), None);
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{2462}'), None);
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('/'), None);
+        // Kawi, Nag Mundari and Eastern Pwo Karen, added after Unicode 14.
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{11f53}'), Some(3));
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{1e4f0}'), Some(0));
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{116e3}'), Some(9));
+        // A Kaktovik numeral is numeric but not a decimal digit.
+        assert_eq!(DigitPolicy::UnicodeDecimal.value('\u{1d2c3}'), None);
+    }
+
+    #[test]
+    fn decimal_zeros_are_numeric() {
+        for &zero in DECIMAL_ZEROS.iter() {
+            for c in zero..zero + 10 {
+                assert!(char::from_u32(c).is_some_and(char::is_numeric), "{:X}", c);
+            }
+        }
+    }
+
+    #[test]
+    fn scanner_non_ascii() {
+        let ascii = Scanner::new(&Vocabulary::english());
+        assert_eq!(ascii.calibration_value("\u{663}é4½"), Ok(44));
+        assert_eq!(
+            ascii.calibration_value("\u{663}½"),
+            Err(CalibrationError::NoDigit)
+        );
+        let unicode = Scanner::new(&Vocabulary::english().digits(DigitPolicy::UnicodeDecimal));
+        assert_eq!(unicode.calibration_value("\u{663}é4½"), Ok(34));
+        assert_eq!(unicode.calibration_value("x\u{967}two\u{ff18}y"), Ok(18));
+        // The last digit's token covers all of its bytes.
+        let token = unicode.last_token("one\u{ff18}").unwrap();
+        assert_eq!((token.start, token.end, token.value), (3, 6, 8));
+        assert_eq!(
+            Vocabulary::parse("digits = unicode"),
+            Ok(Vocabulary::new().digits(DigitPolicy::UnicodeDecimal))
+        );
+    }
+
+    #[test]
+    fn solve_bad_line() {
+        let lines = vec![String::from("1abc2"), String::from("abc")];
+        assert_eq!(
+            Day01.solve(1, &lines),
+            Some(Err(String::from("line 2: no digit")))
+        );
+        assert_eq!(Day01.solve(2, &lines[..1]), Some(Ok(12)));
     }
 }
@@ -1,67 +1,795 @@
-#[derive(Default, Debug, PartialEq, Copy, Clone)]
+use aoc_core::config::OutputFormat;
+use aoc_core::format::{escape_csv, json_string, text_table};
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution, View};
+
+// How many cubes of each colour, in the order the colours were first seen. Colours that aren't
+// listed count as zero.
+#[derive(Default, Debug, Clone)]
 pub struct DiceCount {
-    pub red: i64,
-    pub green: i64,
-    pub blue: i64,
+    counts: Vec<(String, i64)>,
 }
 
-fn parse_round(round: &str) -> DiceCount {
-    let mut count = DiceCount::default();
-    for roll in round.split(", ") {
-        let mut num_and_color = roll.split_whitespace();
-        let num = num_and_color.next().unwrap().parse::<i64>().unwrap();
-        let color = num_and_color.next().unwrap();
-        if color == "red" {
-            count.red += num;
-        } else if color == "green" {
-            count.green += num;
-        } else if color == "blue" {
-            count.blue += num;
+impl DiceCount {
+    pub fn new() -> DiceCount {
+        DiceCount::default()
+    }
+
+    pub fn get(&self, color: &str) -> i64 {
+        self.counts
+            .iter()
+            .find(|(c, _)| c == color)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    pub fn set(&mut self, color: &str, count: i64) {
+        match self.counts.iter_mut().find(|(c, _)| c == color) {
+            Some((_, existing)) => *existing = count,
+            None => self.counts.push((String::from(color), count)),
+        }
+    }
+
+    pub fn add(&mut self, color: &str, count: i64) {
+        self.set(color, self.get(color) + count);
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.counts.iter().map(|(color, _)| color.as_str())
+    }
+
+    // Whether every colour here is at most as many as in `bag`.
+    pub fn fits_in(&self, bag: &DiceCount) -> bool {
+        self.counts
+            .iter()
+            .all(|(color, count)| *count <= bag.get(color))
+    }
+
+    // The larger count of each colour in either.
+    pub fn max(&self, other: &DiceCount) -> DiceCount {
+        let mut result = self.clone();
+        for (color, count) in &other.counts {
+            result.set(color, std::cmp::max(result.get(color), *count));
+        }
+        result
+    }
+
+    // The product of every listed colour's count.
+    pub fn power(&self) -> i64 {
+        self.counts.iter().map(|(_, count)| count).product()
+    }
+
+    // How many cubes of any colour.
+    pub fn total(&self) -> i64 {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    pub fn to_json(&self) -> String {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(color, count)| format!("{}: {}", json_string(color), count))
+            .collect();
+        format!("{{{}}}", counts.join(", "))
+    }
+}
+
+impl<const N: usize> From<[(&str, i64); N]> for DiceCount {
+    fn from(counts: [(&str, i64); N]) -> DiceCount {
+        let mut result = DiceCount::new();
+        for (color, count) in counts {
+            result.add(color, count);
         }
+        result
     }
-    count
 }
 
-fn update_min_set(min_set: &DiceCount, round: &DiceCount) -> DiceCount {
-    DiceCount {
-        red: std::cmp::max(min_set.red, round.red),
-        green: std::cmp::max(min_set.green, round.green),
-        blue: std::cmp::max(min_set.blue, round.blue),
+// Equal when every colour has the same count, so order and listed zeros don't matter.
+impl PartialEq for DiceCount {
+    fn eq(&self, other: &DiceCount) -> bool {
+        self.colors()
+            .chain(other.colors())
+            .all(|color| self.get(color) == other.get(color))
+    }
+}
+
+impl std::fmt::Display for DiceCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(color, count)| format!("{} {}", count, color))
+            .collect();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
+// The colours in the puzzle.
+pub const COLORS: [&str; 3] = ["red", "green", "blue"];
+
+// The bag from part 1.
+pub fn part1_bag() -> DiceCount {
+    DiceCount::from([("red", 12), ("green", 13), ("blue", 14)])
+}
+
+// Parses a round like "3 blue, 4 red". In strict mode only `colors` are allowed, and each of
+// them is listed in the result even if the round doesn't show it.
+fn parse_round(round: &str, colors: Option<&[&str]>) -> Result<DiceCount, String> {
+    let mut count = DiceCount::new();
+    for color in colors.unwrap_or_default() {
+        count.set(color, 0);
     }
+    for roll in round.split(", ") {
+        let (num, color) = roll
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("Expected a count and a colour, got {:?}", roll))?;
+        // Counts are plain digits, so no sign is allowed, even the '+' that i64 would accept.
+        let num = Some(num)
+            .filter(|num| num.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|num| num.parse::<i64>().ok())
+            .ok_or_else(|| format!("Invalid count {:?}", num))?;
+        if color.is_empty() || colors.is_some_and(|colors| !colors.contains(&color)) {
+            return Err(format!("Unknown colour {:?}", color));
+        }
+        count.add(color, num);
+    }
+    Ok(count)
 }
 
-fn min_set_power(count: &DiceCount) -> i64 {
-    count.red * count.green * count.blue
+#[derive(Debug, PartialEq, Clone)]
+pub struct Game {
+    pub id: i64,
+    // The handfuls shown, in order.
+    pub rounds: Vec<DiceCount>,
 }
 
-// Returns the "power" of the line's "minimum set."
-pub fn parse_line(line: &str) -> i64 {
+impl Game {
     // Game 100: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-    let id_and_rounds: Vec<&str> = line.split(": ").collect();
+    pub fn parse(line: &str) -> Result<Game, String> {
+        Game::parse_with(line, None)
+    }
+
+    // Like parse, but rejects colours other than `colors`.
+    pub fn parse_strict(line: &str, colors: &[&str]) -> Result<Game, String> {
+        Game::parse_with(line, Some(colors))
+    }
+
+    fn parse_with(line: &str, colors: Option<&[&str]>) -> Result<Game, String> {
+        let (id, rounds) = line
+            .strip_prefix("Game ")
+            .and_then(|rest| rest.split_once(": "))
+            .ok_or_else(|| String::from("Expected \"Game <id>: <rounds>\""))?;
+        let id = id
+            .parse::<i64>()
+            .map_err(|_| format!("Invalid game id {:?}", id))?;
+        let rounds = rounds
+            .split("; ")
+            .map(|round| parse_round(round, colors))
+            .collect::<Result<Vec<DiceCount>, String>>()?;
+        Ok(Game { id, rounds })
+    }
+
+    // The fewest cubes of each colour that could have been in the bag.
+    pub fn min_set(&self) -> DiceCount {
+        self.rounds
+            .iter()
+            .fold(DiceCount::new(), |min_set, round| min_set.max(round))
+    }
+
+    // Whether the game could have been played with `bag`, and if not, the first round that
+    // shows too many cubes.
+    pub fn feasibility(&self, bag: &DiceCount) -> Feasibility {
+        match self
+            .rounds
+            .iter()
+            .enumerate()
+            .find(|(_, round)| !round.fits_in(bag))
+        {
+            Some((round, count)) => Feasibility::Impossible {
+                round: round + 1,
+                count: count.clone(),
+            },
+            None => Feasibility::Possible,
+        }
+    }
+
+    pub fn is_possible(&self, bag: &DiceCount) -> bool {
+        self.feasibility(bag) == Feasibility::Possible
+    }
+
+    // The first round, numbered from 1, showing more of a colour than `threshold` has. Unlike
+    // a bag, colours missing from the threshold aren't limited.
+    pub fn first_exceeding(&self, threshold: &DiceCount) -> Option<usize> {
+        self.rounds
+            .iter()
+            .position(|round| {
+                threshold
+                    .colors()
+                    .any(|color| round.get(color) > threshold.get(color))
+            })
+            .map(|round| round + 1)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Feasibility {
+    Possible,
+    // Round is numbered from 1.
+    Impossible { round: usize, count: DiceCount },
+}
+
+impl std::fmt::Display for Feasibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Feasibility::Possible => write!(f, "possible"),
+            Feasibility::Impossible { round, count } => {
+                write!(f, "impossible, round {} shows {}", round, count)
+            }
+        }
+    }
+}
+
+// Each game with whether it's possible with `bag`.
+pub fn check_games<'a>(games: &'a [Game], bag: &DiceCount) -> Vec<(&'a Game, Feasibility)> {
+    games
+        .iter()
+        .map(|game| (game, game.feasibility(bag)))
+        .collect()
+}
+
+// Sets a count for each `--set <colour>=<n>`.
+fn counts_from_view(mut counts: DiceCount, view: &View) -> Result<DiceCount, String> {
+    for (color, value) in view.params {
+        let count = value
+            .parse::<i64>()
+            .map_err(|_| format!("Invalid {} count {}", color, value))?;
+        counts.set(color, count);
+    }
+    Ok(counts)
+}
+
+// Reads the bag from `--set <colour>=<n>`, starting from the part 1 bag.
+fn bag_from_view(view: &View) -> Result<DiceCount, String> {
+    counts_from_view(part1_bag(), view)
+}
+
+// Games with a round showing more than `threshold` of some colour, with the first such round.
+pub fn games_exceeding<'a>(games: &'a [Game], threshold: &DiceCount) -> Vec<(&'a Game, usize)> {
+    games
+        .iter()
+        .filter_map(|game| game.first_exceeding(threshold).map(|round| (game, round)))
+        .collect()
+}
+
+// The most cubes of each colour shown in any round of any game.
+pub fn max_per_color(games: &[Game]) -> DiceCount {
+    games
+        .iter()
+        .fold(DiceCount::new(), |max, game| max.max(&game.min_set()))
+}
+
+// The mean number of cubes shown per round, or None if there are no rounds.
+pub fn average_draw_size(games: &[Game]) -> Option<f64> {
+    let rounds: Vec<&DiceCount> = games.iter().flat_map(|game| &game.rounds).collect();
+    if rounds.is_empty() {
+        return None;
+    }
+    let cubes: i64 = rounds.iter().map(|round| round.total()).sum();
+    Some(cubes as f64 / rounds.len() as f64)
+}
+
+// Games with the power of their minimum set, most powerful first.
+pub fn by_power(games: &[Game]) -> Vec<(&Game, i64)> {
+    let mut result: Vec<(&Game, i64)> = games
+        .iter()
+        .map(|game| (game, game.min_set().power()))
+        .collect();
+    result.sort_by_key(|(game, power)| (std::cmp::Reverse(*power), game.id));
+    result
+}
+
+// Renders a query result as a table or as JSON.
+fn query_output(
+    format: OutputFormat,
+    header: &[&str],
+    rows: Vec<Vec<String>>,
+    json: String,
+) -> Result<String, String> {
+    match format {
+        OutputFormat::Text => Ok(text_table(header, &rows)),
+        OutputFormat::Json => Ok(json + "\n"),
+        OutputFormat::Csv => Err(String::from("Queries print a table or JSON")),
+    }
+}
+
+// ln(n!), from a table for n up to `max`. Larger n are summed from the end of the table, so a
+// table that's too small is slower but still right.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LnFactorials {
+    table: Vec<f64>,
+}
+
+impl LnFactorials {
+    pub fn new(max: usize) -> LnFactorials {
+        let mut table = vec![0.0; max + 1];
+        for n in 1..=max {
+            table[n] = table[n - 1] + (n as f64).ln();
+        }
+        LnFactorials { table }
+    }
+
+    pub fn get(&self, n: usize) -> f64 {
+        match self.table.get(n) {
+            Some(&value) => value,
+            None => {
+                let last = self.table.len() - 1;
+                self.table[last] + (last + 1..=n).map(|i| (i as f64).ln()).sum::<f64>()
+            }
+        }
+    }
+
+    // ln of n choose k, or negative infinity if k is out of range.
+    pub fn choose(&self, n: i64, k: i64) -> f64 {
+        if k < 0 || k > n {
+            f64::NEG_INFINITY
+        } else {
+            self.get(n as usize) - self.get(k as usize) - self.get((n - k) as usize)
+        }
+    }
+}
+
+// The log of the chance of seeing the game's rounds if it was played with `bag`. Each round is
+// taken to be a handful drawn without replacement, so it's multivariate hypergeometric, and the
+// cubes go back in the bag between rounds, so rounds are independent. Negative infinity if the
+// bag can't produce the game.
+pub fn log_likelihood(game: &Game, bag: &DiceCount, ln_fact: &LnFactorials) -> f64 {
+    let total = bag.total();
+    if game
+        .rounds
+        .iter()
+        .any(|round| round.total() > total || !round.fits_in(bag))
+    {
+        return f64::NEG_INFINITY;
+    }
+    game.rounds
+        .iter()
+        .map(|round| {
+            let colors: f64 = round
+                .colors()
+                .map(|color| ln_fact.choose(bag.get(color), round.get(color)))
+                .sum();
+            colors - ln_fact.choose(total, round.total())
+        })
+        .sum()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BagEstimate {
+    pub most_likely: DiceCount,
+    pub log_likelihood: f64,
+    // For each colour, the range holding `confidence` of the probability.
+    pub ranges: Vec<(String, i64, i64)>,
+}
+
+// Estimates what was in the bag for one game. Every bag with at least the game's minimum set and
+// at most `max` of each colour is a candidate, and all candidates are equally likely up front.
+// The ranges are the central `confidence` interval of each colour's posterior.
+pub fn estimate_bag(game: &Game, max: i64, confidence: f64) -> BagEstimate {
+    let min_set = game.min_set();
+    let colors: Vec<&str> = min_set.colors().collect();
+    let mins: Vec<i64> = colors.iter().map(|color| min_set.get(color)).collect();
+    let maxes: Vec<i64> = mins.iter().map(|&min| std::cmp::max(min, max)).collect();
+    let ln_fact = LnFactorials::new(maxes.iter().sum::<i64>() as usize);
+    // Walks every candidate like an odometer, keeping only the best so far and, for each colour,
+    // the summed weight of the candidates with each count. Weights are relative to the best
+    // candidate so far, and are rescaled when a better one turns up. The min set comes first and
+    // always fits, so the best is never impossible.
+    let mut best = (mins.clone(), f64::NEG_INFINITY);
+    let mut marginals: Vec<Vec<f64>> = mins
+        .iter()
+        .zip(&maxes)
+        .map(|(min, max)| vec![0.0; (max - min + 1) as usize])
+        .collect();
+    let mut counts = mins.clone();
+    loop {
+        let bag = colors
+            .iter()
+            .zip(&counts)
+            .fold(DiceCount::new(), |mut bag, (color, &count)| {
+                bag.set(color, count);
+                bag
+            });
+        let ll = log_likelihood(game, &bag, &ln_fact);
+        if ll > best.1 {
+            let scale = (best.1 - ll).exp();
+            for weight in marginals.iter_mut().flatten() {
+                *weight *= scale;
+            }
+            best = (counts.clone(), ll);
+        }
+        let weight = (ll - best.1).exp();
+        for (i, marginal) in marginals.iter_mut().enumerate() {
+            marginal[(counts[i] - mins[i]) as usize] += weight;
+        }
+        let Some(i) = (0..counts.len()).find(|&i| counts[i] < maxes[i]) else {
+            break;
+        };
+        counts[i] += 1;
+        counts[..i].copy_from_slice(&mins[..i]);
+    }
+    let (best, best_ll) = best;
+    let tail = (1.0 - confidence) / 2.0;
+    let ranges = colors
+        .iter()
+        .zip(&marginals)
+        .enumerate()
+        .map(|(i, (color, marginal))| {
+            let total_weight: f64 = marginal.iter().sum();
+            let mut cumulative = 0.0;
+            let (mut low, mut high) = (None, maxes[i]);
+            for (offset, weight) in marginal.iter().enumerate() {
+                cumulative += weight / total_weight;
+                if low.is_none() && cumulative > tail {
+                    low = Some(mins[i] + offset as i64);
+                }
+                if cumulative >= 1.0 - tail {
+                    high = mins[i] + offset as i64;
+                    break;
+                }
+            }
+            (String::from(*color), low.unwrap_or(mins[i]), high)
+        })
+        .collect();
+    BagEstimate {
+        most_likely: colors
+            .iter()
+            .zip(&best)
+            .fold(DiceCount::new(), |mut bag, (color, &count)| {
+                bag.set(color, count);
+                bag
+            }),
+        log_likelihood: best_ll,
+        ranges,
+    }
+}
+
+fn param_or<T: std::str::FromStr>(view: &View, key: &str, default: T) -> Result<T, String> {
+    match view.param(key) {
+        Some(value) => value
+            .parse::<T>()
+            .map_err(|_| format!("Invalid {} {}", key, value)),
+        None => Ok(default),
+    }
+}
+
+// `--set max=<n>` bounds each colour, 20 by default, and `--set confidence=<p>` sets the range,
+// 0.95 by default.
+fn estimate_view(view: &View, games: &[Game]) -> Result<String, String> {
+    let max = param_or(view, "max", 20)?;
+    let confidence = param_or(view, "confidence", 0.95)?;
+    if !(0.0..1.0).contains(&confidence) {
+        return Err(format!("Invalid confidence {}", confidence));
+    }
+    let estimates: Vec<(i64, BagEstimate)> = games
+        .iter()
+        .map(|game| (game.id, estimate_bag(game, max, confidence)))
+        .collect();
+    let format_ranges = |estimate: &BagEstimate| -> String {
+        let ranges: Vec<String> = estimate
+            .ranges
+            .iter()
+            .map(|(color, low, high)| format!("{}-{} {}", low, high, color))
+            .collect();
+        ranges.join(", ")
+    };
+    let rows = estimates
+        .iter()
+        .map(|(id, estimate)| {
+            vec![
+                id.to_string(),
+                estimate.most_likely.to_string(),
+                format_ranges(estimate),
+            ]
+        })
+        .collect();
+    let json: Vec<String> = estimates
+        .iter()
+        .map(|(id, estimate)| {
+            let ranges: Vec<String> = estimate
+                .ranges
+                .iter()
+                .map(|(color, low, high)| format!("{}: [{}, {}]", json_string(color), low, high))
+                .collect();
+            format!(
+                "{{\"game\": {}, \"most_likely\": {}, \"ranges\": {{{}}}}}",
+                id,
+                estimate.most_likely.to_json(),
+                ranges.join(", ")
+            )
+        })
+        .collect();
+    let range_header = format!("{}% range", confidence * 100.0);
+    query_output(
+        view.format,
+        &["game", "most likely", &range_header],
+        rows,
+        format!("[{}]", json.join(", ")),
+    )
+}
+
+fn query_view(view: &View, games: &[Game]) -> Option<Result<String, String>> {
+    let result = match view.name {
+        "exceeding" => counts_from_view(DiceCount::new(), view).and_then(|threshold| {
+            if threshold.colors().next().is_none() {
+                return Err(String::from("Set a threshold with --set <colour>=<n>"));
+            }
+            let found = games_exceeding(games, &threshold);
+            let rows = found
+                .iter()
+                .map(|(game, round)| {
+                    let shown = &game.rounds[round - 1];
+                    vec![game.id.to_string(), round.to_string(), shown.to_string()]
+                })
+                .collect();
+            let json: Vec<String> = found
+                .iter()
+                .map(|(game, round)| {
+                    format!(
+                        "{{\"game\": {}, \"round\": {}, \"shown\": {}}}",
+                        game.id,
+                        round,
+                        game.rounds[round - 1].to_json()
+                    )
+                })
+                .collect();
+            let json = format!("[{}]", json.join(", "));
+            query_output(view.format, &["game", "round", "shown"], rows, json)
+        }),
+        "max" => {
+            let max = max_per_color(games);
+            let rows = max
+                .colors()
+                .map(|color| vec![String::from(color), max.get(color).to_string()])
+                .collect();
+            query_output(view.format, &["colour", "max"], rows, max.to_json())
+        }
+        "average" => {
+            let average = average_draw_size(games);
+            let cell = average.map_or(String::from("-"), |average| format!("{:.2}", average));
+            let json = average.map_or(String::from("null"), |average| average.to_string());
+            let json = format!("{{\"average_draw_size\": {}}}", json);
+            query_output(view.format, &["average draw size"], vec![vec![cell]], json)
+        }
+        "by-power" => {
+            let ranked = by_power(games);
+            let rows = ranked
+                .iter()
+                .map(|(game, power)| {
+                    vec![
+                        game.id.to_string(),
+                        game.min_set().to_string(),
+                        power.to_string(),
+                    ]
+                })
+                .collect();
+            let json: Vec<String> = ranked
+                .iter()
+                .map(|(game, power)| {
+                    format!(
+                        "{{\"game\": {}, \"min_set\": {}, \"power\": {}}}",
+                        game.id,
+                        game.min_set().to_json(),
+                        power
+                    )
+                })
+                .collect();
+            let json = format!("[{}]", json.join(", "));
+            query_output(view.format, &["game", "min set", "power"], rows, json)
+        }
+        "estimate" => estimate_view(view, games),
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn feasibility_view(games: &[Game], bag: &DiceCount, format: OutputFormat) -> String {
+    let checked = check_games(games, bag);
+    match format {
+        OutputFormat::Csv => {
+            let mut out = String::from("game,possible,round,shown\n");
+            for (game, feasibility) in checked {
+                out += &match feasibility {
+                    Feasibility::Possible => format!("{},true,,\n", game.id),
+                    Feasibility::Impossible { round, count } => format!(
+                        "{},false,{},{}\n",
+                        game.id,
+                        round,
+                        escape_csv(&count.to_string())
+                    ),
+                };
+            }
+            out
+        }
+        _ => {
+            let mut out = format!("bag: {}\n", bag);
+            for (game, feasibility) in checked {
+                out += &format!("game {}: {}\n", game.id, feasibility);
+            }
+            out + &format!("sum of possible ids: {}\n", sum_possible_ids(games, bag))
+        }
+    }
+}
+
+// Parses every line, naming the first one that doesn't parse. Only `colors` are allowed if
+// they're given.
+pub fn parse_games(lines: &[String], colors: Option<&[&str]>) -> Result<Vec<Game>, String> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            Game::parse_with(line, colors).map_err(|err| format!("line {}: {}", i + 1, err))
+        })
+        .collect()
+}
+
+pub fn sum_possible_ids(games: &[Game], bag: &DiceCount) -> i64 {
+    games
+        .iter()
+        .filter(|game| game.is_possible(bag))
+        .map(|game| game.id)
+        .sum()
+}
+
+pub fn sum_powers(games: &[Game]) -> i64 {
+    games.iter().map(|game| game.min_set().power()).sum()
+}
+
+fn explain_possible(games: &[Game], bag: &DiceCount, trace: &mut Trace) {
+    for (i, game) in games.iter().enumerate() {
+        if !trace.wants(i) {
+            continue;
+        }
+        trace.emit(i, "game", game.id.to_string());
+        match game.feasibility(bag) {
+            Feasibility::Impossible { round, count } => {
+                trace.emit(i, "impossible", format!("round {}: {}", round, count))
+            }
+            Feasibility::Possible => trace.emit(i, "possible", bag.to_string()),
+        }
+    }
+}
 
-    // 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-    let mut min_set = DiceCount::default();
-    for round in id_and_rounds.get(1).unwrap().split("; ") {
-        let count = parse_round(&round);
-        min_set = update_min_set(&min_set, &count);
+fn explain_min_sets(games: &[Game], trace: &mut Trace) {
+    for (i, game) in games.iter().enumerate() {
+        if !trace.wants(i) {
+            continue;
+        }
+        for round in &game.rounds {
+            trace.emit(i, "round", round.to_string());
+        }
+        let min_set = game.min_set();
+        trace.emit(i, "min set", min_set.to_string());
+        trace.emit(i, "power", min_set.power().to_string());
+    }
+}
+
+// Parses games for the views. Any colour is allowed, but every round lists the puzzle's colours
+// and every colour some game shows, so a colour a game never shows counts as 0 in its power, the
+// same as in part 2.
+fn view_games(lines: &[String]) -> Result<Vec<Game>, String> {
+    let games = parse_games(lines, None)?;
+    let mut colors: Vec<&str> = COLORS.to_vec();
+    for color in games
+        .iter()
+        .flat_map(|game| &game.rounds)
+        .flat_map(|round| round.colors())
+    {
+        if !colors.contains(&color) {
+            colors.push(color);
+        }
+    }
+    parse_games(lines, Some(&colors))
+}
+
+fn games(lines: &[String]) -> Result<Vec<Game>, String> {
+    parse_games(lines, Some(&COLORS))
+}
+
+const EXAMPLE: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    fn day(&self) -> &'static str {
+        "day02"
+    }
+
+    fn try_part1(&self, lines: &[String]) -> Option<Result<i64, String>> {
+        Some(games(lines).map(|games| sum_possible_ids(&games, &part1_bag())))
+    }
+
+    fn try_part2(&self, lines: &[String]) -> Option<Result<i64, String>> {
+        Some(games(lines).map(|games| sum_powers(&games)))
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        let games = match games(lines) {
+            Ok(games) => games,
+            Err(err) => return trace.emit(0, "error", err),
+        };
+        match part {
+            1 => explain_possible(&games, &part1_bag(), trace),
+            2 => explain_min_sets(&games, trace),
+            _ => (),
+        }
+    }
+
+    // `feasibility` checks every game against the bag, which `--set <colour>=<n>` changes.
+    // The queries are `exceeding` (with `--set <colour>=<n>` thresholds), `max`, `average`,
+    // `by-power` and `estimate`.
+    fn view(&self, view: &View, lines: &[String]) -> Option<Result<String, String>> {
+        if !matches!(
+            view.name,
+            "feasibility" | "exceeding" | "max" | "average" | "by-power" | "estimate"
+        ) {
+            return None;
+        }
+        let games = match view_games(lines) {
+            Ok(games) => games,
+            Err(err) => return Some(Err(err)),
+        };
+        if view.name == "feasibility" {
+            return Some(
+                bag_from_view(view).map(|bag| feasibility_view(&games, &bag, view.format)),
+            );
+        }
+        query_view(view, &games)
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            1 => Some(Example {
+                input: EXAMPLE,
+                answer: 8,
+            }),
+            2 => Some(Example {
+                input: EXAMPLE,
+                answer: 2286,
+            }),
+            _ => None,
+        }
     }
-    min_set_power(&min_set)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day02::{parse_line, parse_round, DiceCount};
+    use crate::day02::{
+        average_draw_size, bag_from_view, by_power, check_games, estimate_bag, explain_min_sets,
+        explain_possible, feasibility_view, games_exceeding, log_likelihood, max_per_color,
+        parse_games, parse_round, part1_bag, query_view, sum_possible_ids, sum_powers, Day02,
+        DiceCount, Feasibility, Game, LnFactorials, COLORS, EXAMPLE,
+    };
+    use aoc_core::config::OutputFormat;
+    use aoc_core::trace::Trace;
+    use aoc_core::{Solution, View};
+
+    fn example_games() -> Vec<Game> {
+        let lines: Vec<String> = EXAMPLE.lines().map(String::from).collect();
+        parse_games(&lines, Some(&COLORS)).unwrap()
+    }
 
     #[test]
     fn parse_round_red() {
         let round = "4 red";
         assert_eq!(
-            parse_round(&round),
-            DiceCount {
-                red: 4,
-                green: 0,
-                blue: 0,
-            }
+            parse_round(round, Some(&COLORS)),
+            Ok(DiceCount::from([("red", 4), ("green", 0), ("blue", 0)]))
         );
     }
 
@@ -69,12 +797,8 @@ mod tests {
     fn parse_round_green() {
         let round = "4 green";
         assert_eq!(
-            parse_round(&round),
-            DiceCount {
-                red: 0,
-                green: 4,
-                blue: 0,
-            }
+            parse_round(round, Some(&COLORS)),
+            Ok(DiceCount::from([("red", 0), ("green", 4), ("blue", 0)]))
         );
     }
 
@@ -82,18 +806,399 @@ mod tests {
     fn parse_round_blue() {
         let round = "4 blue";
         assert_eq!(
-            parse_round(&round),
-            DiceCount {
-                red: 0,
-                green: 0,
-                blue: 4,
+            parse_round(round, Some(&COLORS)),
+            Ok(DiceCount::from([("red", 0), ("green", 0), ("blue", 4)]))
+        );
+    }
+
+    #[test]
+    fn parse_round_errors() {
+        assert!(parse_round("red", None).is_err());
+        assert!(parse_round("x red", None).is_err());
+        assert!(parse_round("4 ", None).is_err());
+        assert!(parse_round("4 purple", Some(&COLORS)).is_err());
+        assert_eq!(
+            parse_round("-3 red", None),
+            Err(String::from("Invalid count \"-3\""))
+        );
+        assert_eq!(
+            parse_round("+3 red", Some(&COLORS)),
+            Err(String::from("Invalid count \"+3\""))
+        );
+    }
+
+    #[test]
+    fn parse_game_success() {
+        assert_eq!(
+            Game::parse("Game 12: 3 blue, 4 red; 2 green"),
+            Ok(Game {
+                id: 12,
+                rounds: vec![
+                    DiceCount::from([("red", 4), ("green", 0), ("blue", 3)]),
+                    DiceCount::from([("red", 0), ("green", 2), ("blue", 0)]),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_game_errors() {
+        assert!(Game::parse("").is_err());
+        assert!(Game::parse("Game x: 3 blue").is_err());
+        assert!(Game::parse("Round 1: 3 blue").is_err());
+        assert_eq!(
+            parse_games(
+                &[
+                    String::from("Game 1: 3 blue"),
+                    String::from("Game 2: 3 blu")
+                ],
+                Some(&COLORS)
+            ),
+            Err(String::from("line 2: Unknown colour \"blu\""))
+        );
+    }
+
+    #[test]
+    fn game_min_set() {
+        let game = Game::parse_strict(
+            "Game 12: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            &COLORS,
+        )
+        .unwrap();
+        assert_eq!(game.min_set().power(), 48);
+    }
+
+    #[test]
+    fn sum_possible_ids_success() {
+        assert_eq!(sum_possible_ids(&example_games(), &part1_bag()), 8);
+    }
+
+    #[test]
+    fn sum_powers_success() {
+        assert_eq!(sum_powers(&example_games()), 2286);
+    }
+
+    #[test]
+    fn explain_possible_success() {
+        let mut trace = Trace::new(Some(2));
+        explain_possible(&example_games(), &part1_bag(), &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("game", "3"),
+                ("impossible", "round 1: 20 red, 8 green, 6 blue")
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_min_sets_success() {
+        let games = vec![Game::parse_strict(
+            "Game 12: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            &COLORS,
+        )
+        .unwrap()];
+        let mut trace = Trace::new(None);
+        explain_min_sets(&games, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("round", "4 red, 0 green, 3 blue"),
+                ("round", "1 red, 2 green, 6 blue"),
+                ("round", "0 red, 2 green, 0 blue"),
+                ("min set", "4 red, 2 green, 6 blue"),
+                ("power", "48"),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_games_success() {
+        let games = example_games();
+        let checked = check_games(&games, &part1_bag());
+        let results: Vec<(i64, Feasibility)> = checked
+            .iter()
+            .map(|(game, feasibility)| (game.id, feasibility.clone()))
+            .collect();
+        assert_eq!(results[0], (1, Feasibility::Possible));
+        assert_eq!(
+            results[3],
+            (
+                4,
+                Feasibility::Impossible {
+                    round: 3,
+                    count: DiceCount::from([("red", 14), ("green", 3), ("blue", 15)]),
+                }
+            )
+        );
+        let bigger = DiceCount::from([("red", 20), ("green", 13), ("blue", 15)]);
+        assert_eq!(sum_possible_ids(&games, &bigger), 15);
+    }
+
+    #[test]
+    fn bag_from_view_success() {
+        let params = vec![
+            (String::from("red"), String::from("20")),
+            (String::from("blue"), String::from("1")),
+        ];
+        let view = View {
+            name: "feasibility",
+            part: None,
+            format: OutputFormat::Text,
+            params: &params,
+        };
+        assert_eq!(
+            bag_from_view(&view),
+            Ok(DiceCount::from([("red", 20), ("green", 13), ("blue", 1)]))
+        );
+        let params = vec![(String::from("red"), String::from("lots"))];
+        assert!(bag_from_view(&View {
+            params: &params,
+            ..view
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn feasibility_view_success() {
+        let games = example_games();
+        let text = feasibility_view(&games[..3], &part1_bag(), OutputFormat::Text);
+        assert_eq!(
+            text,
+            "bag: 12 red, 13 green, 14 blue
+game 1: possible
+game 2: possible
+game 3: impossible, round 1 shows 20 red, 8 green, 6 blue
+sum of possible ids: 3
+"
+        );
+        let csv = feasibility_view(&games[2..3], &part1_bag(), OutputFormat::Csv);
+        assert_eq!(
+            csv,
+            "game,possible,round,shown\n3,false,1,\"20 red, 8 green, 6 blue\"\n"
+        );
+    }
+
+    #[test]
+    fn any_colors() {
+        let game = Game::parse("Game 7: 2 teal, 1 mauve; 3 teal; 1 mauve, 4 ochre").unwrap();
+        let min_set = game.min_set();
+        assert_eq!(
+            min_set,
+            DiceCount::from([("teal", 3), ("mauve", 1), ("ochre", 4)])
+        );
+        assert_eq!(min_set.to_string(), "3 teal, 1 mauve, 4 ochre");
+        assert_eq!(min_set.power(), 12);
+        let bag = DiceCount::from([("teal", 3), ("mauve", 1)]);
+        assert_eq!(
+            game.feasibility(&bag),
+            Feasibility::Impossible {
+                round: 3,
+                count: DiceCount::from([("mauve", 1), ("ochre", 4)]),
             }
         );
+        assert!(Game::parse_strict("Game 7: 2 teal", &COLORS).is_err());
     }
 
     #[test]
-    fn parse_line_min_set() {
-        let line = "Game 12: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
-        assert_eq!(parse_line(&line), 48);
+    fn dice_count_eq_ignores_zeros() {
+        assert_eq!(
+            DiceCount::from([("red", 4), ("green", 0)]),
+            DiceCount::from([("red", 4)])
+        );
+        assert_ne!(
+            DiceCount::from([("red", 4)]),
+            DiceCount::from([("blue", 4)])
+        );
+    }
+
+    #[test]
+    fn games_exceeding_success() {
+        let games = example_games();
+        let threshold = DiceCount::from([("red", 10)]);
+        let found: Vec<(i64, usize)> = games_exceeding(&games, &threshold)
+            .iter()
+            .map(|(game, round)| (game.id, *round))
+            .collect();
+        assert_eq!(found, vec![(3, 1), (4, 3)]);
+    }
+
+    #[test]
+    fn aggregates_success() {
+        let games = example_games();
+        assert_eq!(
+            max_per_color(&games),
+            DiceCount::from([("red", 20), ("green", 13), ("blue", 15)])
+        );
+        // 159 cubes over 14 rounds.
+        assert_eq!(average_draw_size(&games), Some(159.0 / 14.0));
+        assert_eq!(average_draw_size(&[]), None);
+        let ranked: Vec<(i64, i64)> = by_power(&games)
+            .iter()
+            .map(|(game, power)| (game.id, *power))
+            .collect();
+        assert_eq!(ranked, vec![(3, 1560), (4, 630), (1, 48), (5, 36), (2, 12)]);
+    }
+
+    #[test]
+    fn query_view_success() {
+        let games = example_games();
+        let params = vec![(String::from("blue"), String::from("10"))];
+        let view = View {
+            name: "exceeding",
+            part: None,
+            format: OutputFormat::Text,
+            params: &params,
+        };
+        assert_eq!(
+            query_view(&view, &games),
+            Some(Ok(String::from(
+                "game  round  shown\n4     3      14 red, 3 green, 15 blue\n"
+            )))
+        );
+        let json = View {
+            format: OutputFormat::Json,
+            ..view
+        };
+        assert_eq!(
+            query_view(&json, &games),
+            Some(Ok(String::from(
+                "[{\"game\": 4, \"round\": 3, \"shown\": {\"red\": 14, \"green\": 3, \"blue\": 15}}]\n"
+            )))
+        );
+        let max = View {
+            name: "max",
+            params: &[],
+            ..json
+        };
+        assert_eq!(
+            query_view(&max, &games),
+            Some(Ok(String::from(
+                "{\"red\": 20, \"green\": 13, \"blue\": 15}\n"
+            )))
+        );
+        let no_threshold = View {
+            params: &[],
+            ..view
+        };
+        assert!(matches!(query_view(&no_threshold, &games), Some(Err(_))));
+        assert_eq!(
+            query_view(
+                &View {
+                    name: "bogus",
+                    ..view
+                },
+                &games
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn by_power_view_matches_part2() {
+        let lines = vec![
+            String::from("Game 1: 3 red, 2 green"),
+            String::from("Game 2: 1 red, 1 green; 2 blue"),
+        ];
+        let view = View {
+            name: "by-power",
+            part: None,
+            format: OutputFormat::Json,
+            params: &[],
+        };
+        // Game 1 never shows blue, so its power is 0.
+        assert_eq!(Day02.solve(2, &lines), Some(Ok(2)));
+        let output = Day02.view(&view, &lines).unwrap().unwrap();
+        assert!(output.contains(
+            "\"game\": 1, \"min_set\": {\"red\": 3, \"green\": 2, \"blue\": 0}, \"power\": 0}"
+        ));
+        assert!(output.contains(
+            "\"game\": 2, \"min_set\": {\"red\": 1, \"green\": 1, \"blue\": 2}, \"power\": 2}"
+        ));
+        // Other colours still parse, and count as 0 in games that don't show them.
+        let lines = vec![
+            String::from("Game 1: 1 red, 1 green, 1 blue"),
+            String::from("Game 2: 1 red, 1 green, 1 blue, 2 purple"),
+        ];
+        let output = Day02.view(&view, &lines).unwrap().unwrap();
+        assert!(output.contains("\"game\": 1, \"min_set\": {\"red\": 1, \"green\": 1, \"blue\": 1, \"purple\": 0}, \"power\": 0}"));
+        assert!(output.contains("\"power\": 2}"));
+    }
+
+    #[test]
+    fn log_likelihood_success() {
+        let ln_fact = LnFactorials::new(10);
+        let game = Game::parse("Game 1: 1 red").unwrap();
+        // One red from a bag of 1 red and 1 blue is a coin flip.
+        let bag = DiceCount::from([("red", 1), ("blue", 1)]);
+        assert!((log_likelihood(&game, &bag, &ln_fact) - 0.5f64.ln()).abs() < 1e-9);
+        // Drawing 2 red and 1 blue of 3 red and 2 blue: C(3,2) * C(2,1) / C(5,3) = 6 / 10.
+        let game = Game::parse("Game 1: 2 red, 1 blue").unwrap();
+        let bag = DiceCount::from([("red", 3), ("blue", 2)]);
+        assert!((log_likelihood(&game, &bag, &ln_fact) - 0.6f64.ln()).abs() < 1e-9);
+        let bag = DiceCount::from([("red", 1), ("blue", 2)]);
+        assert_eq!(log_likelihood(&game, &bag, &ln_fact), f64::NEG_INFINITY);
+        // More cubes than the bag holds, in total or of one colour.
+        let game = Game::parse("Game 1: 4 red").unwrap();
+        let bag = DiceCount::from([("red", 3)]);
+        assert_eq!(log_likelihood(&game, &bag, &ln_fact), f64::NEG_INFINITY);
+        let bag = DiceCount::from([("red", 3), ("blue", 5)]);
+        assert_eq!(log_likelihood(&game, &bag, &ln_fact), f64::NEG_INFINITY);
+        // A table that's too small still works.
+        let game = Game::parse("Game 1: 2 red, 1 blue").unwrap();
+        let bag = DiceCount::from([("red", 3), ("blue", 2)]);
+        let small = LnFactorials::new(1);
+        assert!((log_likelihood(&game, &bag, &small) - 0.6f64.ln()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ln_factorials_success() {
+        let ln_fact = LnFactorials::new(3);
+        assert_eq!(ln_fact.get(0), 0.0);
+        assert!((ln_fact.get(3) - 6f64.ln()).abs() < 1e-9);
+        assert!((ln_fact.get(5) - 120f64.ln()).abs() < 1e-9);
+        assert!((ln_fact.choose(5, 2) - 10f64.ln()).abs() < 1e-9);
+        assert_eq!(ln_fact.choose(2, 3), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn estimate_bag_success() {
+        let game = Game::parse("Game 1: 2 red, 1 blue; 1 red, 2 blue; 3 red").unwrap();
+        let estimate = estimate_bag(&game, 6, 0.9);
+        // The all-red round favours more red than the minimum set, up to the bound.
+        assert_eq!(
+            estimate.most_likely,
+            DiceCount::from([("red", 6), ("blue", 3)])
+        );
+        assert_eq!(estimate.ranges.len(), 2);
+        for (color, low, high) in &estimate.ranges {
+            let min = game.min_set().get(color);
+            assert!(min <= *low && low <= high && *high <= 6, "{}", color);
+        }
+        // Rounds showing only red make little blue likely: P(blue = 1) is about 0.63 and
+        // P(blue <= 4) about 0.98.
+        let game = Game::parse("Game 1: 1 red, 1 blue; 3 red; 3 red; 3 red; 3 red").unwrap();
+        let estimate = estimate_bag(&game, 10, 0.9);
+        assert_eq!(estimate.ranges[1], (String::from("blue"), 1, 4));
+    }
+
+    #[test]
+    fn solve_bad_line() {
+        let lines = vec![
+            String::from("Game 1: 3 blue"),
+            String::from("Game 2: 4 purple"),
+        ];
+        assert_eq!(
+            Day02.solve(1, &lines),
+            Some(Err(String::from("line 2: Unknown colour \"purple\"")))
+        );
+        let mut trace = Trace::new(None);
+        Day02.explain(2, &lines, &mut trace);
+        assert_eq!(
+            trace.pairs(),
+            vec![("error", "line 2: Unknown colour \"purple\"")]
+        );
     }
 }
@@ -1,151 +1,1180 @@
-use std::char;
+use aoc_core::config::OutputFormat;
+use aoc_core::format::{dot_string, json_string, text_table};
+use aoc_core::grid::Grid;
+use aoc_core::parse::as_strs;
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution, View};
+use std::io::{self, BufRead, IsTerminal};
 
-fn is_symbol(c: char) -> bool {
-    return c == '*';
+// A number in the schematic, covering columns start..end of its row.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NumberSpan {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+    pub value: i64,
 }
 
-fn find_numbers(line: &str) -> Vec<(usize, usize)> {
-    let ascii_line: Vec<char> = line.chars().collect();
-    let mut numbers = vec![];
-    let mut number_start = None;
-    for (i, c) in ascii_line.iter().enumerate() {
-        if !c.is_numeric() && !number_start.is_none() {
-            numbers.push((number_start.unwrap(), i));
-            number_start = None;
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Symbol {
+    pub row: usize,
+    pub col: usize,
+    pub c: char,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Cell {
+    Empty,
+    // Indexes into the schematic's numbers and symbols.
+    Digit(usize),
+    Symbol(usize),
+}
+
+// Which characters count as symbols for a query. The schematic indexes every character other
+// than a digit or '.', so the set only filters.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub enum SymbolSet {
+    #[default]
+    Any,
+    Only(Vec<char>),
+}
+
+impl SymbolSet {
+    // "any", or the symbol characters themselves, like "*#".
+    pub fn parse(s: &str) -> Result<SymbolSet, String> {
+        if s == "any" {
+            return Ok(SymbolSet::Any);
+        }
+        if s.is_empty() {
+            return Err(String::from("Empty symbol set"));
+        }
+        match s.chars().find(|&c| c.is_ascii_digit() || c == '.') {
+            Some(c) => Err(format!("{:?} can't be a symbol", c)),
+            None => Ok(SymbolSet::Only(s.chars().collect())),
         }
-        if c.is_numeric() && number_start.is_none() {
-            number_start = Some(i)
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            SymbolSet::Any => true,
+            SymbolSet::Only(chars) => chars.contains(&c),
+        }
+    }
+}
+
+// How many numbers a symbol needs next to it for a rule to match.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Count {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Count {
+    pub fn matches(self, n: usize) -> bool {
+        match self {
+            Count::Exactly(count) => n == count,
+            Count::AtLeast(count) => n >= count,
+        }
+    }
+}
+
+pub type ReduceFn = dyn Fn(&[i64]) -> Option<i64>;
+
+// Combines a matching symbol's neighbours into one value. A custom reducer returns None if it
+// can't, like the built-in ones do when the value doesn't fit in an i64.
+pub enum Reducer {
+    Product,
+    Sum,
+    Max,
+    Custom(Box<ReduceFn>),
+}
+
+impl Reducer {
+    pub fn parse(s: &str) -> Result<Reducer, String> {
+        match s {
+            "product" => Ok(Reducer::Product),
+            "sum" => Ok(Reducer::Sum),
+            "max" => Ok(Reducer::Max),
+            _ => Err(format!("Invalid reducer {}", s)),
+        }
+    }
+
+    pub fn reduce(&self, values: &[i64]) -> Option<i64> {
+        match self {
+            Reducer::Product => values.iter().try_fold(1, |acc: i64, &x| acc.checked_mul(x)),
+            Reducer::Sum => values.iter().try_fold(0, |acc: i64, &x| acc.checked_add(x)),
+            Reducer::Max => Some(values.iter().copied().max().unwrap_or(0)),
+            Reducer::Custom(f) => f(values),
+        }
+    }
+}
+
+// Symbols in `symbols` with `count` adjacent numbers, reduced to a value by `reducer`.
+pub struct GearRule {
+    pub symbols: SymbolSet,
+    pub count: Count,
+    pub reducer: Reducer,
+}
+
+impl GearRule {
+    pub fn new(symbols: SymbolSet, count: Count, reducer: Reducer) -> GearRule {
+        GearRule {
+            symbols,
+            count,
+            reducer,
         }
     }
-    if !number_start.is_none() {
-        numbers.push((number_start.unwrap(), ascii_line.len()));
+
+    // The part 2 gear: a '*' next to exactly two numbers, multiplied together.
+    pub fn gear() -> GearRule {
+        GearRule::new(
+            SymbolSet::Only(vec!['*']),
+            Count::Exactly(2),
+            Reducer::Product,
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GearMatch<'a> {
+    pub symbol: &'a Symbol,
+    pub neighbors: Vec<&'a NumberSpan>,
+    // None if the reducer couldn't combine the neighbours.
+    pub value: Option<i64>,
+}
+
+// Appends one row's numbers and symbols, in reading order. Anything other than a digit or '.' is
+// a symbol. Rows must be ASCII, so columns are byte offsets and char offsets alike, and `width`
+// wide if it's given.
+fn parse_row(
+    row: usize,
+    line: &str,
+    width: Option<usize>,
+    numbers: &mut Vec<NumberSpan>,
+    symbols: &mut Vec<Symbol>,
+) -> Result<(), String> {
+    if let Some((col, c)) = line.char_indices().find(|(_, c)| !c.is_ascii()) {
+        return Err(format!(
+            "line {}: non-ASCII character {:?} at column {}",
+            row + 1,
+            c,
+            col + 1
+        ));
+    }
+    if let Some(width) = width.filter(|&w| w != line.len()) {
+        return Err(format!(
+            "line {}: expected {} columns, found {}",
+            row + 1,
+            width,
+            line.len()
+        ));
     }
+    let mut in_number = false;
+    for (col, c) in line.bytes().map(char::from).enumerate() {
+        if let Some(digit) = c.to_digit(10) {
+            if in_number {
+                let span = numbers.last_mut().unwrap();
+                span.end = col + 1;
+                span.value = span
+                    .value
+                    .checked_mul(10)
+                    .and_then(|value| value.checked_add(digit as i64))
+                    .ok_or_else(|| {
+                        format!(
+                            "line {}: number at column {} is too large",
+                            row + 1,
+                            span.start + 1
+                        )
+                    })?;
+            } else {
+                numbers.push(NumberSpan {
+                    row,
+                    start: col,
+                    end: col + 1,
+                    value: digit as i64,
+                });
+            }
+            in_number = true;
+            continue;
+        }
+        in_number = false;
+        if c != '.' {
+            symbols.push(Symbol { row, col, c });
+        }
+    }
+    Ok(())
+}
 
-    numbers
+// The whole engine schematic, with every number and symbol indexed by position.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Schematic {
+    cells: Grid<Cell>,
+    numbers: Vec<NumberSpan>,
+    symbols: Vec<Symbol>,
 }
 
-fn find_symbols(line: &str) -> Vec<usize> {
-    let ascii_line: Vec<char> = line.chars().collect();
-    ascii_line
+impl Schematic {
+    pub fn parse(lines: &[&str]) -> Result<Schematic, String> {
+        let mut numbers = Vec::new();
+        let mut symbols = Vec::new();
+        let mut rows = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            let width = rows.first().map(Vec::len);
+            let (first_number, first_symbol) = (numbers.len(), symbols.len());
+            parse_row(row, line, width, &mut numbers, &mut symbols)?;
+            let mut cells = vec![Cell::Empty; line.len()];
+            for (i, number) in numbers.iter().enumerate().skip(first_number) {
+                cells[number.start..number.end].fill(Cell::Digit(i));
+            }
+            for (i, symbol) in symbols.iter().enumerate().skip(first_symbol) {
+                cells[symbol.col] = Cell::Symbol(i);
+            }
+            rows.push(cells);
+        }
+        // Every row's width was checked by parse_row.
+        let cells = Grid::from_rows(rows).unwrap();
+        Ok(Schematic {
+            cells,
+            numbers,
+            symbols,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    // Numbers in reading order.
+    pub fn numbers(&self) -> &[NumberSpan] {
+        &self.numbers
+    }
+
+    // Symbols in reading order.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    // The number covering (row, col), if any.
+    pub fn number_at(&self, row: usize, col: usize) -> Option<&NumberSpan> {
+        match self.cells.get(row, col) {
+            Some(Cell::Digit(number)) => Some(&self.numbers[*number]),
+            _ => None,
+        }
+    }
+
+    pub fn symbol_at(&self, row: usize, col: usize) -> Option<&Symbol> {
+        match self.cells.get(row, col) {
+            Some(Cell::Symbol(symbol)) => Some(&self.symbols[*symbol]),
+            _ => None,
+        }
+    }
+
+    // The character at (row, col), as it was in the input.
+    pub fn char_at(&self, row: usize, col: usize) -> Option<char> {
+        match self.cells.get(row, col)? {
+            Cell::Empty => Some('.'),
+            Cell::Digit(number) => {
+                let number = &self.numbers[*number];
+                // A power of ten too large for an i64 can only be reached through leading
+                // zeros, so the digit there is 0.
+                let digit = 10_i64
+                    .checked_pow((number.end - col - 1) as u32)
+                    .map_or(0, |power| number.value / power % 10);
+                char::from_digit(digit as u32, 10)
+            }
+            Cell::Symbol(symbol) => Some(self.symbols[*symbol].c),
+        }
+    }
+
+    // The numbers touching (row, col), diagonals included, each once and in reading order.
+    pub fn numbers_adjacent_to(&self, row: usize, col: usize) -> Vec<&NumberSpan> {
+        let mut found: Vec<usize> = self
+            .cells
+            .neighbors(row, col)
+            .filter_map(|(r, c)| match self.cells.get(r, c) {
+                Some(Cell::Digit(number)) => Some(*number),
+                _ => None,
+            })
+            .collect();
+        found.sort();
+        found.dedup();
+        found
+            .into_iter()
+            .map(|number| &self.numbers[number])
+            .collect()
+    }
+
+    // Indexes of the symbols touching any digit of `number`, in reading order.
+    fn symbol_indexes_adjacent_to(&self, number: &NumberSpan) -> Vec<usize> {
+        let rows = number.row.saturating_sub(1)..(number.row + 2).min(self.height());
+        let cols = number.start.saturating_sub(1)..(number.end + 1).min(self.width());
+        rows.flat_map(|r| cols.clone().map(move |c| (r, c)))
+            .filter_map(|(r, c)| match self.cells.get(r, c) {
+                Some(Cell::Symbol(symbol)) => Some(*symbol),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // The symbols touching any digit of `number`, in reading order.
+    pub fn symbols_adjacent_to(&self, number: &NumberSpan) -> Vec<&Symbol> {
+        self.symbol_indexes_adjacent_to(number)
+            .into_iter()
+            .map(|symbol| &self.symbols[symbol])
+            .collect()
+    }
+
+    // Every (number, symbol) pair that touch, as indexes into `numbers()` and `symbols()`.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        self.numbers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, number)| {
+                self.symbol_indexes_adjacent_to(number)
+                    .into_iter()
+                    .map(move |symbol| (i, symbol))
+            })
+            .collect()
+    }
+
+    // Numbers touching at least one symbol in `symbols`, in reading order.
+    pub fn part_numbers(&self, symbols: &SymbolSet) -> Vec<&NumberSpan> {
+        self.numbers
+            .iter()
+            .filter(|number| {
+                self.symbols_adjacent_to(number)
+                    .iter()
+                    .any(|symbol| symbols.contains(symbol.c))
+            })
+            .collect()
+    }
+
+    // Every symbol the rule matches, in reading order.
+    pub fn apply(&self, rule: &GearRule) -> Vec<GearMatch<'_>> {
+        self.symbols
+            .iter()
+            .filter(|symbol| rule.symbols.contains(symbol.c))
+            .filter_map(|symbol| {
+                let neighbors = self.numbers_adjacent_to(symbol.row, symbol.col);
+                if !rule.count.matches(neighbors.len()) {
+                    return None;
+                }
+                let values: Vec<i64> = neighbors.iter().map(|number| number.value).collect();
+                Some(GearMatch {
+                    symbol,
+                    neighbors,
+                    value: rule.reducer.reduce(&values),
+                })
+            })
+            .collect()
+    }
+}
+
+fn schematic(lines: &[String]) -> Result<Schematic, String> {
+    Schematic::parse(&as_strs(lines))
+}
+
+pub fn sum_part_numbers(schematic: &Schematic, symbols: &SymbolSet) -> i64 {
+    schematic
+        .part_numbers(symbols)
         .iter()
-        .enumerate()
-        .filter(|(_i, c)| is_symbol(**c))
-        .map(|(i, _c)| i)
-        .collect()
-}
-
-fn is_connected(gear_pos: usize, number_pos: (usize, usize)) -> bool {
-    let (num_start, num_end) = number_pos;
-    let min_neighbor = if num_start == 0usize {
-        0
-    } else {
-        num_start - 1
+        .map(|number| number.value)
+        .sum()
+}
+
+fn explain_part_numbers(schematic: &Schematic, symbols: &SymbolSet, trace: &mut Trace) {
+    for number in schematic.numbers() {
+        if !trace.wants(number.row) {
+            continue;
+        }
+        let touching: Vec<char> = schematic
+            .symbols_adjacent_to(number)
+            .iter()
+            .map(|symbol| symbol.c)
+            .filter(|&c| symbols.contains(c))
+            .collect();
+        let event = if touching.is_empty() {
+            "not a part"
+        } else {
+            "part number"
+        };
+        trace.emit(
+            number.row,
+            event,
+            format!(
+                "{} at column {}, symbols {:?}",
+                number.value, number.start, touching
+            ),
+        );
+    }
+}
+
+fn too_large(symbol: &Symbol) -> String {
+    format!(
+        "line {}: value of {:?} at column {} is too large",
+        symbol.row + 1,
+        symbol.c,
+        symbol.col + 1
+    )
+}
+
+// Fails if a matching symbol's value, or the total, doesn't fit in an i64.
+pub fn sum_gear_ratios(schematic: &Schematic, rule: &GearRule) -> Result<i64, String> {
+    schematic.apply(rule).iter().try_fold(0, |total: i64, m| {
+        let value = m.value.ok_or_else(|| too_large(m.symbol))?;
+        total
+            .checked_add(value)
+            .ok_or_else(|| String::from("total gear value is too large"))
+    })
+}
+
+// `--set symbols=<chars>` picks the symbols, '*' by default. `--set exactly=<n>` or
+// `--set at_least=<n>` sets the neighbour count, exactly 2 by default, and
+// `--set reduce=<product|sum|max>` how they combine, product by default.
+fn rule_from_view(view: &View) -> Result<GearRule, String> {
+    let mut rule = GearRule::gear();
+    if let Some(symbols) = view.param("symbols") {
+        rule.symbols = SymbolSet::parse(symbols)?;
+    }
+    let count = |key: &str| -> Result<Option<usize>, String> {
+        view.param(key)
+            .map(|n| n.parse().map_err(|_| format!("Invalid {} {}", key, n)))
+            .transpose()
     };
-    let max_neighbor = num_end + 1;
-    gear_pos >= min_neighbor && gear_pos < max_neighbor
+    match (count("exactly")?, count("at_least")?) {
+        (Some(_), Some(_)) => return Err(String::from("Set only one of exactly and at_least")),
+        (Some(n), None) => rule.count = Count::Exactly(n),
+        (None, Some(n)) => rule.count = Count::AtLeast(n),
+        (None, None) => {}
+    }
+    if let Some(reducer) = view.param("reduce") {
+        rule.reducer = Reducer::parse(reducer)?;
+    }
+    Ok(rule)
 }
 
-fn find_vertical_gears(
-    prev_numbers: &Vec<(usize, usize)>,
-    curr_symbols: &Vec<usize>,
-    next_numbers: &Vec<(usize, usize)>,
-) -> i64 {
-    curr_symbols
-        .iter()
-        .filter(|symbol_pos| {
-            prev_numbers
+// Each symbol the view's rule matches, with its neighbours and value, then the total.
+fn gears_view(view: &View, schematic: &Schematic) -> Result<String, String> {
+    let rule = rule_from_view(view)?;
+    let matches = schematic.apply(&rule);
+    let values = |m: &GearMatch| -> Vec<String> {
+        m.neighbors
+            .iter()
+            .map(|number| number.value.to_string())
+            .collect()
+    };
+    match view.format {
+        OutputFormat::Text => {
+            let mut rows: Vec<Vec<String>> = matches
                 .iter()
-                .any(|prev_pos| is_connected(**symbol_pos, *prev_pos))
-                && next_numbers
-                    .iter()
-                    .any(|next_pos| is_connected(**symbol_pos, *next_pos))
-        })
-        .fold(0, |acc, x| acc + (*x as i64))
+                .map(|m| {
+                    vec![
+                        m.symbol.row.to_string(),
+                        m.symbol.col.to_string(),
+                        m.symbol.c.to_string(),
+                        values(m).join(" "),
+                        m.value
+                            .map_or(String::from("too large"), |value| value.to_string()),
+                    ]
+                })
+                .collect();
+            let total = matches
+                .iter()
+                .try_fold(0, |total: i64, m| total.checked_add(m.value?));
+            rows.push(vec![
+                String::from("total"),
+                String::new(),
+                String::new(),
+                String::new(),
+                total.map_or(String::from("too large"), |total| total.to_string()),
+            ]);
+            Ok(text_table(
+                &["row", "col", "symbol", "neighbours", "value"],
+                &rows,
+            ))
+        }
+        OutputFormat::Json => {
+            let json: Vec<String> = matches
+                .iter()
+                .map(|m| {
+                    format!(
+                        "{{\"row\": {}, \"col\": {}, \"symbol\": {}, \"neighbours\": [{}], \"value\": {}}}",
+                        m.symbol.row,
+                        m.symbol.col,
+                        json_string(&m.symbol.c.to_string()),
+                        values(m).join(", "),
+                        m.value.map_or(String::from("null"), |value| value.to_string())
+                    )
+                })
+                .collect();
+            Ok(format!("[{}]\n", json.join(", ")))
+        }
+        OutputFormat::Csv => Err(String::from("gears prints a table or JSON")),
+    }
+}
+
+fn explain_gear_ratios(schematic: &Schematic, trace: &mut Trace) {
+    for symbol in schematic.symbols() {
+        if symbol.c != '*' || !trace.wants(symbol.row) {
+            continue;
+        }
+        let neighbors: Vec<i64> = schematic
+            .numbers_adjacent_to(symbol.row, symbol.col)
+            .iter()
+            .map(|number| number.value)
+            .collect();
+        trace.emit(
+            symbol.row,
+            "symbol",
+            format!("column {}, neighbours {:?}", symbol.col, neighbors),
+        );
+        if let [x, y] = neighbors[..] {
+            let ratio = x
+                .checked_mul(y)
+                .map_or(String::from("too large"), |ratio| ratio.to_string());
+            trace.emit(
+                symbol.row,
+                "gear ratio",
+                format!("{} * {} = {}", x, y, ratio),
+            );
+        }
+    }
 }
 
-fn find_adjacent_numbers(symbol_pos: usize, numbers: &Vec<(usize, usize)>) -> Vec<(usize, usize)> {
-    numbers
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Style {
+    Plain,
+    // A number touching a symbol.
+    Part,
+    // A number touching no symbol.
+    Loose,
+    Gear,
+    // A number next to a gear.
+    GearNeighbor,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Plain => "\x1b[0m",
+            Style::Part => "\x1b[32m",
+            Style::Loose => "\x1b[31m",
+            Style::Gear => "\x1b[1;33m",
+            Style::GearNeighbor => "\x1b[1;36m",
+        }
+    }
+}
+
+// Draws the schematic with ANSI colours: part numbers green, loose numbers red, and the symbols
+// `rule` matches in yellow with their numbers in cyan. Without `color` it's the schematic as
+// it was read.
+pub fn render(schematic: &Schematic, rule: &GearRule, color: bool) -> String {
+    let mut number_styles: Vec<Style> = schematic
+        .numbers()
         .iter()
-        .filter(|(num_start, num_end)| {
-            let min_neighbor = if *num_start == 0usize {
-                0
+        .map(|number| {
+            if schematic.symbols_adjacent_to(number).is_empty() {
+                Style::Loose
             } else {
-                num_start - 1
-            };
-            let max_neighbor = num_end + 1;
-            symbol_pos >= min_neighbor && symbol_pos < max_neighbor
+                Style::Part
+            }
         })
-        .map(|(num_start, num_end)| (*num_start, *num_end))
-        .collect()
+        .collect();
+    let mut symbol_styles = vec![Style::Plain; schematic.symbols().len()];
+    for m in schematic.apply(rule) {
+        if let Some(Cell::Symbol(symbol)) = schematic.cells.get(m.symbol.row, m.symbol.col) {
+            symbol_styles[*symbol] = Style::Gear;
+        }
+        for number in m.neighbors {
+            if let Some(Cell::Digit(i)) = schematic.cells.get(number.row, number.start) {
+                number_styles[*i] = Style::GearNeighbor;
+            }
+        }
+    }
+    let mut out = String::new();
+    for row in 0..schematic.height() {
+        let mut current = Style::Plain;
+        for col in 0..schematic.width() {
+            let style = match schematic.cells.get(row, col) {
+                Some(Cell::Digit(i)) => number_styles[*i],
+                Some(Cell::Symbol(i)) => symbol_styles[*i],
+                _ => Style::Plain,
+            };
+            if color && style != current {
+                out += style.code();
+                current = style;
+            }
+            out.push(schematic.char_at(row, col).unwrap());
+        }
+        if current != Style::Plain {
+            out += Style::Plain.code();
+        }
+        out.push('\n');
+    }
+    out
 }
 
-fn count_adjacent_numbers(symbol_pos: usize, numbers: &Vec<(usize, usize)>) -> usize {
-    find_adjacent_numbers(symbol_pos, numbers).len()
+// `--set color=<auto|always|never>` picks whether to colour, auto by default, which colours only
+// when stdout is a terminal. The gear rule comes from the same settings as the gears view.
+fn render_view(view: &View, schematic: &Schematic) -> Result<String, String> {
+    if view.format != OutputFormat::Text {
+        return Err(String::from("render only prints text"));
+    }
+    let color = match view.param("color").unwrap_or("auto") {
+        "auto" => std::io::stdout().is_terminal(),
+        "always" => true,
+        "never" => false,
+        other => return Err(format!("Invalid color {}", other)),
+    };
+    Ok(render(schematic, &rule_from_view(view)?, color))
 }
 
-fn parse_i64_substr(line: &str, num_pos: (usize, usize)) -> i64 {
-    let (num_start, num_end) = num_pos;
-    if num_start == num_end {
-        panic!("num_start == num_end");
+// The adjacency graph in Graphviz DOT. Positions follow the grid, so `neato -n` lays it out like
+// the schematic.
+pub fn graph_dot(schematic: &Schematic) -> String {
+    let mut out = String::from("graph schematic {\n");
+    for (i, number) in schematic.numbers().iter().enumerate() {
+        out += &format!(
+            "  n{} [label={}, pos=\"{},{}!\"];\n",
+            i,
+            dot_string(&number.value.to_string()),
+            number.start,
+            -(number.row as i64)
+        );
+    }
+    for (i, symbol) in schematic.symbols().iter().enumerate() {
+        out += &format!(
+            "  s{} [label={}, shape=box, pos=\"{},{}!\"];\n",
+            i,
+            dot_string(&symbol.c.to_string()),
+            symbol.col,
+            -(symbol.row as i64)
+        );
     }
-    let substr = line.get(num_start..num_end).unwrap();
-    // println!("{},{}: \"{}\"", num_start, num_end, substr);
-    substr.parse::<i64>().unwrap()
+    for (number, symbol) in schematic.edges() {
+        out += &format!("  n{} -- s{};\n", number, symbol);
+    }
+    out + "}\n"
 }
 
-fn parse_neighbor_numbers(line: &str, number_positions: Vec<(usize, usize)>) -> Vec<i64> {
-    number_positions
+// The adjacency graph as JSON nodes and edges.
+pub fn graph_json(schematic: &Schematic) -> String {
+    let numbers = schematic.numbers().iter().enumerate().map(|(i, number)| {
+        format!(
+            "{{\"id\": \"n{}\", \"kind\": \"number\", \"row\": {}, \"col\": {}, \"len\": {}, \"value\": {}}}",
+            i,
+            number.row,
+            number.start,
+            number.end - number.start,
+            number.value
+        )
+    });
+    let symbols = schematic.symbols().iter().enumerate().map(|(i, symbol)| {
+        format!(
+            "{{\"id\": \"s{}\", \"kind\": \"symbol\", \"row\": {}, \"col\": {}, \"symbol\": {}}}",
+            i,
+            symbol.row,
+            symbol.col,
+            json_string(&symbol.c.to_string())
+        )
+    });
+    let nodes: Vec<String> = numbers.chain(symbols).collect();
+    let edges: Vec<String> = schematic
+        .edges()
         .iter()
-        .map(|&num_pos| parse_i64_substr(line, num_pos))
-        .collect()
+        .map(|(number, symbol)| format!("{{\"from\": \"n{}\", \"to\": \"s{}\"}}", number, symbol))
+        .collect();
+    format!(
+        "{{\"nodes\": [{}], \"edges\": [{}]}}\n",
+        nodes.join(", "),
+        edges.join(", ")
+    )
 }
 
-pub fn scan_line(prev_line: &str, line: &str, next_line: &str) -> i64 {
-    let ascii_line: Vec<char> = line.chars().collect();
-    if prev_line.len() != ascii_line.len() || next_line.len() != ascii_line.len() {
-        panic!("Invalid len");
+// DOT by default, or JSON with `--format json`.
+fn graph_view(view: &View, schematic: &Schematic) -> Result<String, String> {
+    match view.format {
+        OutputFormat::Text => Ok(graph_dot(schematic)),
+        OutputFormat::Json => Ok(graph_json(schematic)),
+        OutputFormat::Csv => Err(String::from("graph prints DOT or JSON")),
     }
+}
+
+// What the streaming scanner reports, as soon as a row's neighbours have been read.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StreamEvent {
+    PartNumber(NumberSpan),
+    // A symbol the gear rule matched, with its neighbours in reading order.
+    Gear {
+        symbol: Symbol,
+        neighbors: Vec<NumberSpan>,
+        value: i64,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct StreamTotal {
+    pub rows: usize,
+    pub part_numbers: i64,
+    pub gear_ratios: i64,
+}
 
-    let curr_symbols = find_symbols(line);
-    let prev_numbers = find_numbers(prev_line);
-    let curr_numbers = find_numbers(line);
-    let next_numbers = find_numbers(next_line);
+#[derive(Debug, Default)]
+struct StreamRow {
+    numbers: Vec<NumberSpan>,
+    symbols: Vec<Symbol>,
+}
+
+// The symbols touching a number spanning start..end. `symbols` is one row's, in column order.
+fn symbols_near(symbols: &[Symbol], start: usize, end: usize) -> &[Symbol] {
+    let low = symbols.partition_point(|symbol| symbol.col + 1 < start);
+    let high = symbols.partition_point(|symbol| symbol.col <= end);
+    &symbols[low..high]
+}
+
+// The numbers touching column `col`. `numbers` is one row's, in column order.
+fn numbers_near(numbers: &[NumberSpan], col: usize) -> &[NumberSpan] {
+    let low = numbers.partition_point(|number| number.end < col);
+    let high = numbers.partition_point(|number| number.start <= col + 1);
+    &numbers[low..high]
+}
 
-    let gears = curr_symbols
+// Reports the middle row's part numbers and gears. `window` is the rows around it, in order,
+// without the ones past the edges. Fails if a gear's value or the gear total is too large.
+fn finish_row(
+    window: &[&StreamRow],
+    middle: &StreamRow,
+    symbols: &SymbolSet,
+    rule: &GearRule,
+    total: &mut StreamTotal,
+    on_event: &mut impl FnMut(StreamEvent),
+) -> Result<(), String> {
+    for number in &middle.numbers {
+        let is_part = window.iter().any(|row| {
+            symbols_near(&row.symbols, number.start, number.end)
+                .iter()
+                .any(|symbol| symbols.contains(symbol.c))
+        });
+        if is_part {
+            total.part_numbers += number.value;
+            on_event(StreamEvent::PartNumber(*number));
+        }
+    }
+    for symbol in middle
+        .symbols
         .iter()
-        .filter(|&&sym| {
-            count_adjacent_numbers(sym, &prev_numbers)
-                + count_adjacent_numbers(sym, &curr_numbers)
-                + count_adjacent_numbers(sym, &next_numbers)
-                == 2
-        })
-        .map(|&sym| {
-            let prev_neighbors =
-                parse_neighbor_numbers(prev_line, find_adjacent_numbers(sym, &prev_numbers));
-            let mut curr_neighbors =
-                parse_neighbor_numbers(line, find_adjacent_numbers(sym, &curr_numbers));
-            let mut next_neighbors =
-                parse_neighbor_numbers(next_line, find_adjacent_numbers(sym, &next_numbers));
-            let mut neighbors = Vec::from(prev_neighbors.as_slice());
-            neighbors.append(&mut curr_neighbors);
-            neighbors.append(&mut next_neighbors);
-
-            let x = neighbors.get(0).unwrap();
-            let y = neighbors.get(1).unwrap();
-            x * y
+        .filter(|symbol| rule.symbols.contains(symbol.c))
+    {
+        let neighbors: Vec<NumberSpan> = window
+            .iter()
+            .flat_map(|row| numbers_near(&row.numbers, symbol.col))
+            .copied()
+            .collect();
+        if !rule.count.matches(neighbors.len()) {
+            continue;
+        }
+        let values: Vec<i64> = neighbors.iter().map(|number| number.value).collect();
+        let value = rule
+            .reducer
+            .reduce(&values)
+            .ok_or_else(|| too_large(symbol))?;
+        total.gear_ratios = total
+            .gear_ratios
+            .checked_add(value)
+            .ok_or_else(|| String::from("total gear value is too large"))?;
+        on_event(StreamEvent::Gear {
+            symbol: *symbol,
+            neighbors,
+            value,
         });
+    }
+    Ok(())
+}
 
-    gears.fold(0, |acc, x| acc + x)
+// Scans a schematic row by row, keeping only three rows in memory, so it doesn't need to fit.
+// Part numbers are numbers touching a symbol in `symbols`, gears are what `rule` matches. Each
+// row is reported once the next one has been read, and the last at the end of the stream. Bad
+// rows stop the scan with an InvalidData error.
+pub fn scan_reader(
+    mut reader: impl BufRead,
+    symbols: &SymbolSet,
+    rule: &GearRule,
+    mut on_event: impl FnMut(StreamEvent),
+) -> io::Result<StreamTotal> {
+    let mut ring: [StreamRow; 3] = Default::default();
+    let mut total = StreamTotal::default();
+    let mut width = None;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let done = reader.read_until(b'\n', &mut buf)? == 0;
+        if !done {
+            let row = total.rows;
+            let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let invalid = |err: String| io::Error::new(io::ErrorKind::InvalidData, err);
+            let line = std::str::from_utf8(line)
+                .map_err(|_| invalid(format!("line {}: invalid UTF-8", row + 1)))?;
+            let next = &mut ring[row % 3];
+            next.numbers.clear();
+            next.symbols.clear();
+            parse_row(row, line, width, &mut next.numbers, &mut next.symbols).map_err(invalid)?;
+            width = Some(line.len());
+            total.rows += 1;
+        }
+        // The row before the one just read has both its neighbours now. At the end there's no
+        // next row, so the last row is finished instead.
+        let middle = if done {
+            total.rows.checked_sub(1)
+        } else {
+            total.rows.checked_sub(2)
+        };
+        if let Some(middle) = middle {
+            let window: Vec<&StreamRow> = (middle.saturating_sub(1)..(middle + 2).min(total.rows))
+                .map(|row| &ring[row % 3])
+                .collect();
+            finish_row(
+                &window,
+                &ring[middle % 3],
+                symbols,
+                rule,
+                &mut total,
+                &mut on_event,
+            )
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        }
+        if done {
+            return Ok(total);
+        }
+    }
+}
+
+const EXAMPLE: &str = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    fn day(&self) -> &'static str {
+        "day03"
+    }
+
+    fn try_part1(&self, lines: &[String]) -> Option<Result<i64, String>> {
+        Some(schematic(lines).map(|schematic| sum_part_numbers(&schematic, &SymbolSet::Any)))
+    }
+
+    fn try_part2(&self, lines: &[String]) -> Option<Result<i64, String>> {
+        Some(schematic(lines).and_then(|schematic| sum_gear_ratios(&schematic, &GearRule::gear())))
+    }
+
+    // Keeps three rows in memory at a time, however long the input.
+    fn solve_stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<io::Result<i64>> {
+        let scan = |reader| scan_reader(reader, &SymbolSet::Any, &GearRule::gear(), |_| ());
+        match part {
+            1 => Some(scan(reader).map(|total| total.part_numbers)),
+            2 => Some(scan(reader).map(|total| total.gear_ratios)),
+            _ => None,
+        }
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        let schematic = match schematic(lines) {
+            Ok(schematic) => schematic,
+            Err(err) => return trace.emit(0, "error", err),
+        };
+        match part {
+            1 => explain_part_numbers(&schematic, &SymbolSet::Any, trace),
+            2 => explain_gear_ratios(&schematic, trace),
+            _ => {}
+        }
+    }
+
+    fn view(&self, view: &View, lines: &[String]) -> Option<Result<String, String>> {
+        let show = match view.name {
+            "gears" => gears_view,
+            "render" => render_view,
+            "graph" => graph_view,
+            _ => return None,
+        };
+        Some(Schematic::parse(&as_strs(lines)).and_then(|schematic| show(view, &schematic)))
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            1 => Some(Example {
+                input: EXAMPLE,
+                answer: 4361,
+            }),
+            2 => Some(Example {
+                input: EXAMPLE,
+                answer: 467835,
+            }),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day03::{parse_i64_substr, scan_line};
+    use crate::day03::{
+        explain_gear_ratios, explain_part_numbers, gears_view, graph_dot, graph_json, graph_view,
+        render, render_view, scan_reader, sum_gear_ratios, sum_part_numbers, Count, Day03,
+        GearRule, NumberSpan, Reducer, Schematic, StreamEvent, StreamTotal, Symbol, SymbolSet,
+        EXAMPLE,
+    };
+    use aoc_core::config::OutputFormat;
+    use aoc_core::trace::Trace;
+    use aoc_core::{Solution, View};
+    use std::io;
+
+    fn scan_line(prev_line: &str, curr_line: &str, next_line: &str) -> i64 {
+        let schematic = Schematic::parse(&[prev_line, curr_line, next_line]).unwrap();
+        sum_gear_ratios(&schematic, &GearRule::gear()).unwrap()
+    }
+
+    fn example() -> Schematic {
+        Schematic::parse(&EXAMPLE.lines().collect::<Vec<&str>>()).unwrap()
+    }
+
+    #[test]
+    fn parse_success() {
+        let schematic = Schematic::parse(&["..123", "#.4.."]).unwrap();
+        assert_eq!(
+            schematic.numbers(),
+            &[
+                NumberSpan {
+                    row: 0,
+                    start: 2,
+                    end: 5,
+                    value: 123,
+                },
+                NumberSpan {
+                    row: 1,
+                    start: 2,
+                    end: 3,
+                    value: 4,
+                },
+            ]
+        );
+        assert_eq!(
+            schematic.symbols(),
+            &[Symbol {
+                row: 1,
+                col: 0,
+                c: '#',
+            }]
+        );
+        assert_eq!(schematic.number_at(0, 3).map(|n| n.value), Some(123));
+        assert_eq!(schematic.number_at(1, 1), None);
+        assert_eq!(
+            Schematic::parse(&["..1", "."]),
+            Err(String::from("line 2: expected 3 columns, found 1"))
+        );
+    }
+
+    #[test]
+    fn parse_non_ascii() {
+        // 'é' is two bytes and '€' three, so byte and char columns would disagree.
+        assert_eq!(
+            Schematic::parse(&["12é*5"]),
+            Err(String::from("line 1: non-ASCII character 'é' at column 3"))
+        );
+        assert_eq!(
+            Schematic::parse(&["....", "1*€."]),
+            Err(String::from("line 2: non-ASCII character '€' at column 3"))
+        );
+        // Same char count as the first row, but more bytes.
+        assert!(Schematic::parse(&["1*..", "1*é."]).is_err());
+        // Non-ASCII digits aren't digits or symbols.
+        assert!(Schematic::parse(&["٣*1"]).is_err());
+    }
+
+    #[test]
+    fn parse_too_large() {
+        assert!(Schematic::parse(&["9223372036854775807*"]).is_ok());
+        assert_eq!(
+            Schematic::parse(&["*.9223372036854775808"]),
+            Err(String::from("line 1: number at column 3 is too large"))
+        );
+    }
+
+    #[test]
+    fn adjacency_success() {
+        let schematic = example();
+        let values: Vec<i64> = schematic
+            .numbers_adjacent_to(4, 3)
+            .iter()
+            .map(|n| n.value)
+            .collect();
+        assert_eq!(values, vec![617]);
+        let values: Vec<i64> = schematic
+            .numbers_adjacent_to(1, 3)
+            .iter()
+            .map(|n| n.value)
+            .collect();
+        assert_eq!(values, vec![467, 35]);
+        let n114 = schematic.number_at(0, 5).unwrap();
+        assert!(schematic.symbols_adjacent_to(n114).is_empty());
+        let n633 = schematic.number_at(2, 6).unwrap();
+        assert_eq!(
+            schematic.symbols_adjacent_to(n633),
+            vec![&Symbol {
+                row: 3,
+                col: 6,
+                c: '#',
+            }]
+        );
+    }
+
+    #[test]
+    fn symbol_set_parse() {
+        assert_eq!(SymbolSet::parse("any"), Ok(SymbolSet::Any));
+        assert_eq!(SymbolSet::parse("*#"), Ok(SymbolSet::Only(vec!['*', '#'])));
+        assert!(SymbolSet::parse("").is_err());
+        assert!(SymbolSet::parse("*.").is_err());
+        assert!(SymbolSet::parse("1").is_err());
+    }
+
+    #[test]
+    fn sum_part_numbers_success() {
+        let schematic = example();
+        assert_eq!(sum_part_numbers(&schematic, &SymbolSet::Any), 4361);
+        // 467, 35, 617, 755 and 598 touch a '*'.
+        assert_eq!(
+            sum_part_numbers(&schematic, &SymbolSet::Only(vec!['*'])),
+            467 + 35 + 617 + 755 + 598
+        );
+        assert_eq!(sum_part_numbers(&schematic, &SymbolSet::Only(vec!['@'])), 0);
+    }
+
+    #[test]
+    fn explain_part_numbers_success() {
+        let schematic = Schematic::parse(&["467..114..", "...*......"]).unwrap();
+        let mut trace = Trace::new(Some(0));
+        explain_part_numbers(&schematic, &SymbolSet::Any, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("part number", "467 at column 0, symbols ['*']"),
+                ("not a part", "114 at column 5, symbols []"),
+            ]
+        );
+    }
+
+    #[test]
+    fn sum_gear_ratios_success() {
+        assert_eq!(sum_gear_ratios(&example(), &GearRule::gear()), Ok(467835));
+    }
 
     #[test]
-    fn parse_i64_substr_success() {
-        let line = "..123";
-        assert_eq!(parse_i64_substr(&line, (2, 5)), 123);
+    fn apply_rules() {
+        let schematic = example();
+        let gears = schematic.apply(&GearRule::gear());
+        assert_eq!(gears.len(), 2);
+        assert_eq!((gears[0].symbol.row, gears[0].symbol.col), (1, 3));
+        assert_eq!(gears[0].value, Some(467 * 35));
+        let values: Vec<i64> = gears[1].neighbors.iter().map(|n| n.value).collect();
+        assert_eq!(values, vec![755, 598]);
+
+        // Every symbol touching a number, summed.
+        let rule = GearRule::new(SymbolSet::Any, Count::AtLeast(1), Reducer::Sum);
+        assert_eq!(sum_gear_ratios(&schematic, &rule), Ok(4361));
+
+        let rule = GearRule::new(SymbolSet::Only(vec!['*']), Count::Exactly(1), Reducer::Max);
+        let matches = schematic.apply(&rule);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].symbol.row, matches[0].value), (4, Some(617)));
+
+        let rule = GearRule::new(
+            SymbolSet::Any,
+            Count::Exactly(2),
+            Reducer::Custom(Box::new(|values| values[0].checked_sub(values[1]))),
+        );
+        assert_eq!(
+            sum_gear_ratios(&schematic, &rule),
+            Ok((467 - 35) + (755 - 598))
+        );
+    }
+
+    #[test]
+    fn apply_rules_too_large() {
+        assert_eq!(Reducer::Product.reduce(&[i64::MAX, 2]), None);
+        assert_eq!(Reducer::Sum.reduce(&[i64::MAX, 1]), None);
+        assert_eq!(Reducer::Max.reduce(&[i64::MAX, 1]), Some(i64::MAX));
+
+        let schematic = Schematic::parse(&[
+            "9999999999.9999999999",
+            "..........*..........",
+            "9999999999.9999999999",
+        ])
+        .unwrap();
+        let rule = GearRule::new(SymbolSet::Any, Count::AtLeast(3), Reducer::Product);
+        let matches = schematic.apply(&rule);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].value, None);
+        assert_eq!(
+            sum_gear_ratios(&schematic, &rule),
+            Err(String::from(
+                "line 2: value of '*' at column 11 is too large"
+            ))
+        );
+        let rule = GearRule::new(SymbolSet::Any, Count::AtLeast(3), Reducer::Sum);
+        assert_eq!(sum_gear_ratios(&schematic, &rule), Ok(4 * 9999999999));
+    }
+
+    #[test]
+    fn gears_view_success() {
+        let schematic = example();
+        let params = vec![(String::from("reduce"), String::from("sum"))];
+        let mut view = View {
+            name: "gears",
+            part: None,
+            format: OutputFormat::Text,
+            params: &params,
+        };
+        assert_eq!(
+            gears_view(&view, &schematic),
+            Ok(String::from(
+                "row    col  symbol  neighbours  value
+1      3    *       467 35      502
+8      5    *       755 598     1353
+total                           1855
+"
+            ))
+        );
+        view.format = OutputFormat::Json;
+        let params = vec![
+            (String::from("symbols"), String::from("#")),
+            (String::from("at_least"), String::from("1")),
+        ];
+        view.params = &params;
+        assert_eq!(
+            gears_view(&view, &schematic),
+            Ok(String::from(
+                "[{\"row\": 3, \"col\": 6, \"symbol\": \"#\", \"neighbours\": [633], \"value\": 633}]\n"
+            ))
+        );
+        let params = vec![
+            (String::from("exactly"), String::from("1")),
+            (String::from("at_least"), String::from("1")),
+        ];
+        view.params = &params;
+        assert!(gears_view(&view, &schematic).is_err());
     }
 
     #[test]
@@ -233,4 +1262,253 @@ mod tests {
             assert_eq!(scan_line(&prev_line, &curr_line, &next_line), 144);
         }
     }
+
+    #[test]
+    fn explain_gear_ratios_success() {
+        let schematic = Schematic::parse(&["467..114..", "...*......", "..35..633."]).unwrap();
+        let mut trace = Trace::new(Some(1));
+        explain_gear_ratios(&schematic, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("symbol", "column 3, neighbours [467, 35]"),
+                ("gear ratio", "467 * 35 = 16345"),
+            ]
+        );
+
+        let schematic = Schematic::parse(&["9999999999.", "..........*", "9999999999."]).unwrap();
+        let mut trace = Trace::new(Some(1));
+        explain_gear_ratios(&schematic, &mut trace);
+        assert_eq!(
+            trace.pairs()[1],
+            ("gear ratio", "9999999999 * 9999999999 = too large")
+        );
+    }
+
+    #[test]
+    fn render_plain() {
+        assert_eq!(render(&example(), &GearRule::gear(), false), EXAMPLE);
+        // Leading zeros survive.
+        let schematic = Schematic::parse(&["007*", "...1"]).unwrap();
+        assert_eq!(schematic.char_at(0, 1), Some('0'));
+        assert_eq!(render(&schematic, &GearRule::gear(), false), "007*\n...1\n");
+        // More leading zeros than an i64 has digits.
+        let row = format!("{}1*", "0".repeat(24));
+        let schematic = Schematic::parse(&[row.as_str()]).unwrap();
+        assert_eq!(schematic.char_at(0, 0), Some('0'));
+        assert_eq!(render(&schematic, &GearRule::gear(), false), row + "\n");
+    }
+
+    #[test]
+    fn render_color() {
+        let schematic = Schematic::parse(&["12*3.", "....4", "5#..."]).unwrap();
+        assert_eq!(
+            render(&schematic, &GearRule::gear(), true),
+            "\x1b[1;36m12\x1b[1;33m*\x1b[1;36m3\x1b[0m.\n\
+             ....\x1b[31m4\x1b[0m\n\
+             \x1b[32m5\x1b[0m#...\n"
+        );
+        let params = vec![(String::from("color"), String::from("never"))];
+        let mut view = View {
+            name: "render",
+            part: None,
+            format: OutputFormat::Text,
+            params: &params,
+        };
+        assert_eq!(
+            render_view(&view, &schematic),
+            Ok(String::from("12*3.\n....4\n5#...\n"))
+        );
+        view.format = OutputFormat::Json;
+        assert!(render_view(&view, &schematic).is_err());
+    }
+
+    #[test]
+    fn edges_success() {
+        let schematic = Schematic::parse(&["1*2.", "..#3"]).unwrap();
+        // 1 and 2 touch the '*', 2 and 3 the '#', and 2 also the '*'.
+        assert_eq!(schematic.edges(), vec![(0, 0), (1, 0), (1, 1), (2, 1)]);
+        assert_eq!(example().edges().len(), 8);
+    }
+
+    #[test]
+    fn graph_export() {
+        let schematic = Schematic::parse(&["1*.", "..\""]).unwrap();
+        assert_eq!(
+            graph_dot(&schematic),
+            "graph schematic {
+  n0 [label=\"1\", pos=\"0,0!\"];
+  s0 [label=\"*\", shape=box, pos=\"1,0!\"];
+  s1 [label=\"\\\"\", shape=box, pos=\"2,-1!\"];
+  n0 -- s0;
+}
+"
+        );
+        assert_eq!(
+            graph_json(&schematic),
+            "{\"nodes\": [\
+             {\"id\": \"n0\", \"kind\": \"number\", \"row\": 0, \"col\": 0, \"len\": 1, \"value\": 1}, \
+             {\"id\": \"s0\", \"kind\": \"symbol\", \"row\": 0, \"col\": 1, \"symbol\": \"*\"}, \
+             {\"id\": \"s1\", \"kind\": \"symbol\", \"row\": 1, \"col\": 2, \"symbol\": \"\\\"\"}], \
+             \"edges\": [{\"from\": \"n0\", \"to\": \"s0\"}]}\n"
+        );
+        let view = View {
+            name: "graph",
+            part: None,
+            format: OutputFormat::Csv,
+            params: &[],
+        };
+        assert!(graph_view(&view, &schematic).is_err());
+    }
+
+    fn scan(input: &str) -> io::Result<(StreamTotal, Vec<StreamEvent>)> {
+        let mut events = Vec::new();
+        let total = scan_reader(
+            input.as_bytes(),
+            &SymbolSet::Any,
+            &GearRule::gear(),
+            |event| events.push(event),
+        )?;
+        Ok((total, events))
+    }
+
+    #[test]
+    fn scan_reader_example() {
+        let (total, events) = scan(EXAMPLE).unwrap();
+        assert_eq!(
+            total,
+            StreamTotal {
+                rows: 10,
+                part_numbers: 4361,
+                gear_ratios: 467835,
+            }
+        );
+        let schematic = example();
+        let parts: Vec<NumberSpan> = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::PartNumber(number) => Some(*number),
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<NumberSpan> = schematic
+            .part_numbers(&SymbolSet::Any)
+            .into_iter()
+            .copied()
+            .collect();
+        assert_eq!(parts, expected);
+        assert!(events.contains(&StreamEvent::Gear {
+            symbol: Symbol {
+                row: 8,
+                col: 5,
+                c: '*',
+            },
+            neighbors: vec![
+                *schematic.number_at(7, 6).unwrap(),
+                *schematic.number_at(9, 5).unwrap(),
+            ],
+            value: 755 * 598,
+        }));
+    }
+
+    #[test]
+    fn scan_reader_edges() {
+        // Numbers on the first and last rows, no trailing newline.
+        let (total, _) = scan("12*.\n...3\n5*7.").unwrap();
+        assert_eq!(total.rows, 3);
+        assert_eq!(total.part_numbers, 12 + 3 + 5 + 7);
+        assert_eq!(total.gear_ratios, 12 * 3 + 5 * 7);
+        // A single row, with CRLF.
+        let (total, events) = scan("4*5\r\n").unwrap();
+        assert_eq!((total.part_numbers, total.gear_ratios), (9, 20));
+        assert_eq!(events.len(), 3);
+        assert_eq!(scan("").unwrap().0, StreamTotal::default());
+    }
+
+    #[test]
+    fn scan_reader_errors() {
+        let err = scan("1*..\n..\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2: expected 4 columns, found 2");
+        let err = scan("1*é.\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1: non-ASCII character 'é' at column 3"
+        );
+        let err = scan("99999999999999999999\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: number at column 1 is too large");
+        let err = scan("9999999999.\n..........*\n9999999999.\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "line 2: value of '*' at column 11 is too large"
+        );
+    }
+
+    #[test]
+    fn scan_reader_matches_schematic() {
+        // A pseudo-random grid, dense enough for numbers to share symbols across rows.
+        let mut state: u64 = 0x5eed;
+        let mut lines = Vec::new();
+        for _ in 0..40 {
+            let line: String = (0..40)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    match (state >> 33) % 10 {
+                        0..=3 => '.',
+                        4..=7 => char::from(b'0' + (state >> 40) as u8 % 10),
+                        8 => '*',
+                        _ => '#',
+                    }
+                })
+                .collect();
+            lines.push(line);
+        }
+        let input = lines.join("\n");
+        let schematic =
+            Schematic::parse(&lines.iter().map(String::as_str).collect::<Vec<_>>()).unwrap();
+        let rule = GearRule::new(SymbolSet::Any, Count::AtLeast(2), Reducer::Sum);
+        let total =
+            scan_reader(input.as_bytes(), &SymbolSet::Only(vec!['#']), &rule, |_| {}).unwrap();
+        assert_eq!(
+            total.part_numbers,
+            sum_part_numbers(&schematic, &SymbolSet::Only(vec!['#']))
+        );
+        assert_eq!(Ok(total.gear_ratios), sum_gear_ratios(&schematic, &rule));
+    }
+
+    #[test]
+    fn solve_stream_success() {
+        assert_eq!(
+            Day03
+                .solve_stream(1, &mut EXAMPLE.as_bytes())
+                .unwrap()
+                .unwrap(),
+            4361
+        );
+        assert_eq!(
+            Day03
+                .solve_stream(2, &mut EXAMPLE.as_bytes())
+                .unwrap()
+                .unwrap(),
+            467835
+        );
+        assert!(Day03.solve_stream(3, &mut EXAMPLE.as_bytes()).is_none());
+        assert!(Day03
+            .solve_stream(1, &mut "1*\n.".as_bytes())
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn solve_bad_line() {
+        let lines = vec![String::from("1*."), String::from("..")];
+        assert_eq!(
+            Day03.solve(2, &lines),
+            Some(Err(String::from("line 2: expected 3 columns, found 2")))
+        );
+    }
 }
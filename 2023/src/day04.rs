@@ -1,3 +1,6 @@
+use aoc_core::parse::{as_strs, parse_numbers};
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution};
 use std::collections::HashSet;
 
 struct Scratchcard {
@@ -8,18 +11,8 @@ struct Scratchcard {
 impl Scratchcard {
     fn from_line(line: &str) -> Scratchcard {
         let mut parts = line.split(": ").nth(1).unwrap().split(" | ");
-        let winning_numbers = parts
-            .next()
-            .unwrap()
-            .split_whitespace()
-            .map(|s| s.parse::<i64>().unwrap())
-            .collect();
-        let have_numbers = parts
-            .next()
-            .unwrap()
-            .split_whitespace()
-            .map(|s| s.parse::<i64>().unwrap())
-            .collect();
+        let winning_numbers = parse_numbers(parts.next().unwrap()).into_iter().collect();
+        let have_numbers = parse_numbers(parts.next().unwrap()).into_iter().collect();
         Scratchcard {
             have_numbers,
             winning_numbers,
@@ -31,30 +24,125 @@ impl Scratchcard {
             .intersection(&self.have_numbers)
             .count() as i64
     }
+
+    // One point for the first match, doubled for each match after it.
+    fn points(&self) -> i64 {
+        match self.count_winning_numbers() {
+            0 => 0,
+            n => 1 << (n - 1),
+        }
+    }
 }
 
-fn count_winning_numbers(lines: &[&str]) -> Vec<i64> {
+fn parse_cards(lines: &[&str]) -> Vec<Scratchcard> {
     lines
         .iter()
         .map(|line| Scratchcard::from_line(line))
-        .map(|card| card.count_winning_numbers())
         .collect()
 }
 
+fn count_winning_numbers(lines: &[&str]) -> Vec<i64> {
+    parse_cards(lines)
+        .iter()
+        .map(Scratchcard::count_winning_numbers)
+        .collect()
+}
+
+pub fn sum_points(lines: &[&str]) -> i64 {
+    parse_cards(lines).iter().map(Scratchcard::points).sum()
+}
+
+fn explain_points(lines: &[&str], trace: &mut Trace) {
+    for (i, card) in parse_cards(lines).iter().enumerate() {
+        if !trace.wants(i) {
+            continue;
+        }
+        trace.emit(i, "matches", card.count_winning_numbers().to_string());
+        trace.emit(i, "points", card.points().to_string());
+    }
+}
+
 pub fn count_total_scorecards(lines: &[&str]) -> i64 {
     let winning_numbers = count_winning_numbers(lines);
-    let mut card_count = vec![1].repeat(winning_numbers.len());
+    let mut card_count = [1].repeat(winning_numbers.len());
     for (i, num) in winning_numbers.iter().enumerate() {
         for j in (i + 1)..(i + *num as usize + 1) {
             card_count[j] += card_count[i]
         }
     }
-    card_count.iter().fold(0, |acc, x| acc + x)
+    card_count.iter().sum()
+}
+
+// Each card's copies come from the cards before it, so every card is processed, but only the
+// requested ones are reported.
+fn explain_scorecards(lines: &[&str], trace: &mut Trace) {
+    let winning_numbers = count_winning_numbers(lines);
+    let mut card_count = [1].repeat(winning_numbers.len());
+    for (i, num) in winning_numbers.iter().enumerate() {
+        trace.emit(i, "matches", num.to_string());
+        trace.emit(i, "copies", card_count[i].to_string());
+        for j in (i + 1)..(i + *num as usize + 1) {
+            card_count[j] += card_count[i];
+            trace.emit(j, "won", format!("{} from card {}", card_count[i], i + 1));
+        }
+    }
+}
+
+const EXAMPLE: &str = "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    fn day(&self) -> &'static str {
+        "day04"
+    }
+
+    fn part1(&self, lines: &[String]) -> Option<i64> {
+        Some(sum_points(&as_strs(lines)))
+    }
+
+    fn part2(&self, lines: &[String]) -> Option<i64> {
+        Some(count_total_scorecards(&as_strs(lines)))
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        match part {
+            1 => explain_points(&as_strs(lines), trace),
+            2 => explain_scorecards(&as_strs(lines), trace),
+            _ => {}
+        }
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            1 => Some(Example {
+                input: EXAMPLE,
+                answer: 13,
+            }),
+            2 => Some(Example {
+                input: EXAMPLE,
+                answer: 30,
+            }),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
-    use crate::day04::{count_total_scorecards, count_winning_numbers, Scratchcard};
+    use crate::day04::{
+        count_total_scorecards, count_winning_numbers, explain_points, explain_scorecards,
+        sum_points, Scratchcard, EXAMPLE,
+    };
+    use aoc_core::trace::Trace;
     use std::collections::HashSet;
 
     #[test]
@@ -88,6 +176,23 @@ mod tests {
         assert_eq!(Scratchcard::from_line(&line).count_winning_numbers(), 2);
     }
 
+    #[test]
+    fn scratchcard_points() {
+        let points = |line| Scratchcard::from_line(line).points();
+        assert_eq!(
+            points("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
+            8
+        );
+        assert_eq!(
+            points("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83"),
+            1
+        );
+        assert_eq!(
+            points("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
+            0
+        );
+    }
+
     #[test]
     fn count_winning_numbers_test() {
         let lines = vec![
@@ -113,4 +218,39 @@ mod tests {
         ];
         assert_eq!(count_total_scorecards(&lines), 30);
     }
+
+    #[test]
+    fn sum_points_test() {
+        let lines: Vec<&str> = EXAMPLE.lines().collect();
+        assert_eq!(sum_points(&lines), 13);
+    }
+
+    #[test]
+    fn explain_points_test() {
+        let lines = vec![
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+            "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+        ];
+        let mut trace = Trace::new(Some(0));
+        explain_points(&lines, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(events, vec![("matches", "4"), ("points", "8")]);
+    }
+
+    #[test]
+    fn explain_scorecards_test() {
+        let lines: Vec<&str> = EXAMPLE.lines().collect();
+        let mut trace = Trace::new(Some(2));
+        explain_scorecards(&lines, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("won", "1 from card 1"),
+                ("won", "2 from card 2"),
+                ("matches", "2"),
+                ("copies", "4"),
+            ]
+        );
+    }
 }
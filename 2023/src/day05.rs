@@ -1,3 +1,7 @@
+use aoc_core::parse::parse_numbers;
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution};
+
 #[derive(Debug, PartialEq)]
 struct AlmanacRange {
     destination_start: i64,
@@ -5,11 +9,6 @@ struct AlmanacRange {
     len: i64,
 }
 
-// Parse an usize from s or panic.
-fn parse_usize(s: &str) -> usize {
-    s.parse::<usize>().unwrap()
-}
-
 // Parse an i64 from s or panic.
 fn parse_i64(s: &str) -> i64 {
     s.parse::<i64>().unwrap()
@@ -52,17 +51,11 @@ pub struct SeedRange {
 }
 
 pub fn read_seeds(line: &str) -> Vec<SeedRange> {
-    let seed_ranges: Vec<i64> = line
-        .split(": ")
-        .nth(1)
-        .unwrap()
-        .split_whitespace()
-        .map(parse_i64)
-        .collect();
+    let seed_ranges = parse_numbers(line.split(": ").nth(1).unwrap());
     let mut result: Vec<SeedRange> = Vec::new();
-    for i in (0..seed_ranges.len()).into_iter().filter(|x| x % 2 == 0) {
+    for i in (0..seed_ranges.len()).filter(|x| x % 2 == 0) {
         result.push(SeedRange {
-            start: seed_ranges[i] as i64,
+            start: seed_ranges[i],
             len: seed_ranges[i + 1],
         });
     }
@@ -70,19 +63,18 @@ pub fn read_seeds(line: &str) -> Vec<SeedRange> {
 }
 
 pub fn read_maps(lines: &[String]) -> Vec<AlmanacMap> {
-    let seeds = &lines[0];
     // The maps start on the third line.
     let mut maps_lines: Vec<Vec<&str>> = Vec::new();
     let mut map_lines: Vec<&str> = Vec::new();
-    for i in 2..lines.len() {
-        if lines[i] == "" {
+    for line in &lines[2..] {
+        if line.is_empty() {
             maps_lines.push(map_lines);
             map_lines = Vec::new();
         } else {
-            map_lines.push(lines[i].as_str());
+            map_lines.push(line.as_str());
         }
     }
-    if map_lines.len() > 0 {
+    if !map_lines.is_empty() {
         maps_lines.push(map_lines);
     }
 
@@ -92,35 +84,144 @@ pub fn read_maps(lines: &[String]) -> Vec<AlmanacMap> {
         .collect()
 }
 
+// Maps value through the first range of map that contains it, or leaves it unchanged.
+fn map_value(map: &AlmanacMap, value: i64) -> i64 {
+    for almanac_range in map.ranges.iter() {
+        if value >= almanac_range.source_start
+            && value < almanac_range.source_start + almanac_range.len
+        {
+            let offset = value - almanac_range.source_start;
+            return almanac_range.destination_start + offset;
+        }
+    }
+    value
+}
+
 pub fn map_seeds(seed_ranges: &[SeedRange], maps: &[AlmanacMap]) -> i64 {
-    let mut smallest_result = std::i64::MAX;
+    let mut smallest_result = i64::MAX;
     for seed_range in seed_ranges {
-        println!("{:?}", seed_range);
         for seed_i in seed_range.start..seed_range.start + seed_range.len {
-            let before = seed_i;
-            let mut mapped = seed_i;
-            for map in maps {
-                for almanac_range in map.ranges.iter() {
-                    if mapped >= almanac_range.source_start
-                        && mapped < almanac_range.source_start + almanac_range.len
-                    {
-                        let offset = mapped - almanac_range.source_start;
-                        mapped = almanac_range.destination_start + offset;
-                        break;
-                    }
-                    // println!("{} -> {}", before, mapped);
-                }
-            }
+            let mapped = maps
+                .iter()
+                .fold(seed_i, |mapped, map| map_value(map, mapped));
             smallest_result = std::cmp::min(mapped, smallest_result);
         }
-        println!("smallest_result={}", smallest_result);
     }
     smallest_result
 }
 
+// The categories the maps convert between, in order.
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+// Follows the first seed of each range through every map.
+fn explain_seed_ranges(lines: &[String], trace: &mut Trace) {
+    let seeds = read_seeds(&lines[0]);
+    let maps = read_maps(lines);
+    for (i, seed_range) in seeds.iter().enumerate() {
+        if !trace.wants(i) {
+            continue;
+        }
+        trace.emit(
+            i,
+            "range",
+            format!(
+                "{}..{}",
+                seed_range.start,
+                seed_range.start + seed_range.len
+            ),
+        );
+        let mut mapped = seed_range.start;
+        trace.emit(i, CATEGORIES[0], mapped.to_string());
+        for (map, category) in maps.iter().zip(CATEGORIES[1..].iter()) {
+            mapped = map_value(map, mapped);
+            trace.emit(i, category, mapped.to_string());
+        }
+    }
+}
+
+const EXAMPLE: &str = "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    fn day(&self) -> &'static str {
+        "day05"
+    }
+
+    fn part2(&self, lines: &[String]) -> Option<i64> {
+        let seeds = read_seeds(&lines[0]);
+        let maps = read_maps(lines);
+        Some(map_seeds(&seeds, &maps))
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        if part == 2 {
+            explain_seed_ranges(lines, trace);
+        }
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            2 => Some(Example {
+                input: EXAMPLE,
+                answer: 46,
+            }),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::needless_borrow, clippy::useless_vec)]
 mod tests {
-    use crate::day05::{map_seeds, read_maps, read_seeds, AlmanacMap, AlmanacRange, SeedRange};
+    use crate::day05::{
+        explain_seed_ranges, map_seeds, read_maps, read_seeds, AlmanacMap, AlmanacRange, SeedRange,
+        EXAMPLE,
+    };
+    use aoc_core::trace::Trace;
 
     #[test]
     fn read_seeds_success() {
@@ -165,6 +266,10 @@ mod tests {
         );
     }
 
+    fn example_lines() -> Vec<String> {
+        EXAMPLE.lines().map(String::from).collect()
+    }
+
     #[test]
     fn map_seeds_success() {
         let lines: Vec<String> = vec![
@@ -209,4 +314,26 @@ mod tests {
         let maps = read_maps(&lines);
         assert_eq!(map_seeds(&seeds, &maps), 46);
     }
+
+    #[test]
+    fn explain_seed_ranges_success() {
+        let lines = example_lines();
+        let mut trace = Trace::new(Some(0));
+        explain_seed_ranges(&lines, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("range", "79..93"),
+                ("seed", "79"),
+                ("soil", "81"),
+                ("fertilizer", "81"),
+                ("water", "81"),
+                ("light", "74"),
+                ("temperature", "78"),
+                ("humidity", "78"),
+                ("location", "82")
+            ]
+        );
+    }
 }
@@ -1,3 +1,7 @@
+use aoc_core::parse::{as_strs, parse_numbers};
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution};
+
 #[derive(Default, PartialEq, Debug)]
 struct RaceRecords {
     time: Vec<i64>,
@@ -5,12 +9,7 @@ struct RaceRecords {
 }
 
 fn parse_line(line: &str) -> Vec<i64> {
-    line.split(":")
-        .nth(1)
-        .unwrap()
-        .split_whitespace()
-        .map(|n| n.parse::<i64>().unwrap())
-        .collect()
+    parse_numbers(line.split(":").nth(1).unwrap())
 }
 
 fn parse_lines(lines: &[&str]) -> RaceRecords {
@@ -58,7 +57,8 @@ fn is_winning(time: i64, button_time: i64, record_distance: i64) -> bool {
 
 // -x^2 + t*x = d
 
-fn count_winning_strategies(time: i64, record_distance: i64) -> i64 {
+// The button times that beat the record, as a half-open range.
+fn winning_range(time: i64, record_distance: i64) -> (i64, i64) {
     let mut start = 0;
     while !is_winning(time, start, record_distance) {
         start += 1;
@@ -69,7 +69,12 @@ fn count_winning_strategies(time: i64, record_distance: i64) -> i64 {
         i += 1;
     }
 
-    i - start
+    (start, i)
+}
+
+fn count_winning_strategies(time: i64, record_distance: i64) -> i64 {
+    let (start, end) = winning_range(time, record_distance);
+    end - start
 }
 
 pub fn do_part1(line_strings: &[String]) -> i64 {
@@ -77,12 +82,7 @@ pub fn do_part1(line_strings: &[String]) -> i64 {
     let records = parse_lines(&lines);
     let mut result = 1;
     for i in 0..records.time.len() {
-        let winning = count_winning_strategies(records.time[i], records.distance[i]);
-        println!(
-            "time: {} distance: {} winning: {}",
-            records.time[i], records.distance[i], winning
-        );
-        result *= winning;
+        result *= count_winning_strategies(records.time[i], records.distance[i]);
     }
     result
 }
@@ -93,12 +93,85 @@ pub fn do_part2(line_strings: &[String]) -> i64 {
     count_winning_strategies(records.time, records.distance)
 }
 
+fn explain_race(item: usize, time: i64, record_distance: i64, trace: &mut Trace) {
+    let (start, end) = winning_range(time, record_distance);
+    trace.emit(item, "time", time.to_string());
+    trace.emit(item, "record", record_distance.to_string());
+    trace.emit(item, "hold", format!("{}..{}", start, end));
+    trace.emit(item, "winning", (end - start).to_string());
+}
+
+fn explain_part1(lines: &[&str], trace: &mut Trace) {
+    let records = parse_lines(lines);
+    for i in 0..records.time.len() {
+        if trace.wants(i) {
+            explain_race(i, records.time[i], records.distance[i], trace);
+        }
+    }
+}
+
+fn explain_part2(lines: &[&str], trace: &mut Trace) {
+    let records = parse_lines2(lines);
+    if trace.wants(0) {
+        explain_race(0, records.time, records.distance, trace);
+    }
+}
+
+const EXAMPLE: &str = "\
+Time:      7  15   30
+Distance:  9  40  200
+";
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    fn day(&self) -> &'static str {
+        "day06"
+    }
+
+    fn part1(&self, lines: &[String]) -> Option<i64> {
+        Some(do_part1(lines))
+    }
+
+    fn part2(&self, lines: &[String]) -> Option<i64> {
+        Some(do_part2(lines))
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        match part {
+            1 => explain_part1(&as_strs(lines), trace),
+            2 => explain_part2(&as_strs(lines), trace),
+            _ => (),
+        }
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            1 => Some(Example {
+                input: EXAMPLE,
+                answer: 288,
+            }),
+            2 => Some(Example {
+                input: EXAMPLE,
+                answer: 71503,
+            }),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
+#[allow(
+    clippy::bool_assert_comparison,
+    clippy::needless_borrow,
+    clippy::useless_vec
+)]
 mod tests {
     use crate::day06::{
-        calculate_final_distance, count_winning_strategies, do_part1, do_part2, is_winning,
-        parse_line, parse_lines, RaceRecords,
+        calculate_final_distance, count_winning_strategies, do_part1, do_part2, explain_part1,
+        explain_part2, is_winning, parse_line, parse_lines, RaceRecords,
     };
+    use aoc_core::trace::Trace;
 
     #[test]
     fn parse_line_success() {
@@ -170,4 +243,37 @@ mod tests {
             .collect();
         assert_eq!(do_part2(&lines), 71503);
     }
+
+    #[test]
+    fn explain_part1_success() {
+        let lines = vec!["Time:      7  15   30", "Distance:  9  40  200"];
+        let mut trace = Trace::new(Some(2));
+        explain_part1(&lines, &mut trace);
+        assert_eq!(
+            trace.pairs(),
+            vec![
+                ("time", "30"),
+                ("record", "200"),
+                ("hold", "11..20"),
+                ("winning", "9")
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_part2_success() {
+        let lines = vec!["Time:      7  15   30", "Distance:  9  40  200"];
+        let mut trace = Trace::new(Some(0));
+        explain_part2(&lines, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("time", "71530"),
+                ("record", "940200"),
+                ("hold", "14..71517"),
+                ("winning", "71503")
+            ]
+        );
+    }
 }
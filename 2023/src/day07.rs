@@ -1,3 +1,7 @@
+use aoc_core::parse::as_strs;
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution};
+
 #[derive(Debug, Default, PartialEq, Eq)]
 struct Hand {
     cards: Vec<char>,
@@ -101,17 +105,13 @@ impl Hand {
 
     fn hand_type_from_card_count(card_count: &[i64]) -> Option<HandType> {
         use HandType::*;
-        if card_count.iter().any(|&count| count == 5) {
+        if card_count.contains(&5) {
             Some(FiveOfAKind)
-        } else if card_count.iter().any(|&count| count == 4)
-            && card_count.iter().any(|&count| count == 1)
-        {
+        } else if card_count.contains(&4) && card_count.contains(&1) {
             Some(FourOfAKind)
-        } else if card_count.iter().any(|&count| count == 3)
-            && card_count.iter().any(|&count| count == 2)
-        {
+        } else if card_count.contains(&3) && card_count.contains(&2) {
             Some(FullHouse)
-        } else if card_count.iter().any(|&count| count == 3)
+        } else if card_count.contains(&3)
             && card_count
                 .iter()
                 .filter(|&&count| count < 2 && count > 0)
@@ -137,8 +137,7 @@ impl Hand {
     }
 
     fn count_cards(&self) -> Vec<i64> {
-        let mut count = Vec::new();
-        count.resize(get_card_num('A') + 1, 0);
+        let mut count = vec![0; get_card_num('A') + 1];
         for &card in self.cards.as_slice() {
             count[get_card_num(card)] += 1;
         }
@@ -183,12 +182,82 @@ pub fn do_part1(lines: &[&str]) -> i64 {
         .iter()
         .enumerate()
         .map(|(i, &ihand)| ((i as i64) + 1) * hands[ihand].bid)
-        .fold(0, |acc, x| acc + x)
+        .sum()
+}
+
+fn explain_hands(lines: &[&str], trace: &mut Trace) {
+    let hands = parse_hands(lines);
+    let mut indices = (0..lines.len()).collect::<Vec<usize>>();
+    indices.sort_by_key(|&i| &hands[i]);
+    for (rank, &i) in indices.iter().enumerate() {
+        if !trace.wants(i) {
+            continue;
+        }
+        let hand = &hands[i];
+        let card_count = hand.count_cards();
+        trace.emit(i, "cards", hand.cards.iter().collect());
+        if let Some(hand_type) = Hand::hand_type_from_card_count(&card_count) {
+            trace.emit(i, "type", format!("{:?}", hand_type));
+        }
+        let num_jokers = card_count[get_card_num('J')];
+        if num_jokers > 0 {
+            trace.emit(
+                i,
+                "jokers",
+                format!("{} -> {:?}", num_jokers, hand.hand_type()),
+            );
+        }
+        let rank = rank as i64 + 1;
+        trace.emit(i, "rank", rank.to_string());
+        trace.emit(
+            i,
+            "winnings",
+            format!("{} * {} = {}", rank, hand.bid, rank * hand.bid),
+        );
+    }
+}
+
+const EXAMPLE: &str = "\
+32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+";
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    fn day(&self) -> &'static str {
+        "day07"
+    }
+
+    fn part2(&self, lines: &[String]) -> Option<i64> {
+        Some(do_part1(&as_strs(lines)))
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        if part == 2 {
+            explain_hands(&as_strs(lines), trace);
+        }
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            2 => Some(Example {
+                input: EXAMPLE,
+                answer: 5905,
+            }),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
-    use crate::day07::{do_part1, parse_hands, Hand, HandType};
+    use crate::day07::{do_part1, explain_hands, parse_hands, Hand, HandType};
+    use aoc_core::trace::Trace;
 
     // 32T3K 765
     // T55J5 684
@@ -277,4 +346,28 @@ mod tests {
         ];
         assert_eq!(do_part1(lines.as_slice()), 5905);
     }
+
+    #[test]
+    fn explain_hands_success() {
+        let lines = vec![
+            "32T3K 765",
+            "T55J5 684",
+            "KK677 28",
+            "KTJJT 220",
+            "QQQJA 483",
+        ];
+        let mut trace = Trace::new(Some(3));
+        explain_hands(lines.as_slice(), &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("cards", "KTJJT"),
+                ("type", "TwoPair"),
+                ("jokers", "2 -> FourOfAKind"),
+                ("rank", "5"),
+                ("winnings", "5 * 220 = 1100")
+            ]
+        );
+    }
 }
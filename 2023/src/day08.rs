@@ -1,3 +1,7 @@
+use aoc_core::math::lcm;
+use aoc_core::parse::as_strs;
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution};
 use std::collections::HashMap;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -76,62 +80,105 @@ fn advance_key(
     })
 }
 
-fn advance_keys(
-    pos: &Vec<String>,
-    network: &HashMap<String, (String, String)>,
-    direction: &Direction,
-) -> Vec<String> {
-    pos.iter()
-        .map(|p| advance_key(p, network, direction))
-        .collect()
+fn starting_keys(map: &Map) -> Vec<String> {
+    let mut keys: Vec<String> = map
+        .network
+        .keys()
+        .filter(|k| k.ends_with("A"))
+        .map(String::from)
+        .collect();
+    keys.sort();
+    keys
 }
 
-fn gcd(x: i64, y: i64) -> i64 {
-    let mut a = x;
-    let mut b = y;
-    while a != b {
-        if a > b {
-            a = a - b;
-        } else {
-            b = b - a;
-        }
+// Counts the steps from start to the first key ending in Z, calling on_step with each move.
+fn count_steps(map: &Map, start: &str, mut on_step: impl FnMut(&str, &Direction, &str)) -> i64 {
+    let mut pos = String::from(start);
+    let mut idir = 0;
+    let mut count = 0;
+    while !pos.ends_with("Z") {
+        let direction = &map.instructions[idir];
+        let next = advance_key(&pos, &map.network, direction);
+        on_step(&pos, direction, &next);
+        pos = next;
+        idir = (idir + 1) % map.instructions.len();
+        count += 1;
     }
-    a
-}
-
-fn lcm(x: i64, y: i64) -> i64 {
-    (x * y).abs() / gcd(x, y)
+    count
 }
 
 pub fn do_part2(lines: &[&str]) -> i64 {
     let map = Map::parse(lines);
-    let starting_keys: Vec<String> = map
-        .network
-        .keys()
-        .filter(|k| k.ends_with("A"))
-        .map(String::from)
-        .collect();
-    println!("Starting keys: {:?}", starting_keys);
+    let starting_keys = starting_keys(&map);
     starting_keys
         .iter()
-        .map(|start_pos| {
-            let mut pos = String::from(start_pos);
-            let mut idir = 0;
-            let mut count = 0;
-            while !pos.ends_with("Z") {
-                pos = advance_key(&pos, &map.network, &map.instructions[idir]);
-                // println!("{:?}", pos);
-                idir = (idir + 1) % map.instructions.len();
-                count += 1;
-            }
-            count
-        })
+        .map(|start_pos| count_steps(&map, start_pos, |_, _, _| ()))
         .fold(1, lcm)
 }
 
+fn explain_paths(lines: &[&str], trace: &mut Trace) {
+    let map = Map::parse(lines);
+    let mut result = 1;
+    for (i, start_pos) in starting_keys(&map).iter().enumerate() {
+        let count = if trace.wants(i) {
+            trace.emit(i, "start", start_pos.clone());
+            count_steps(&map, start_pos, |pos, direction, next| {
+                trace.emit(i, "step", format!("{} -{:?}-> {}", pos, direction, next))
+            })
+        } else {
+            count_steps(&map, start_pos, |_, _, _| ())
+        };
+        result = lcm(result, count);
+        trace.emit(i, "steps", count.to_string());
+        trace.emit(i, "lcm so far", result.to_string());
+    }
+}
+
+const EXAMPLE: &str = "\
+LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+";
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    fn day(&self) -> &'static str {
+        "day08"
+    }
+
+    fn part2(&self, lines: &[String]) -> Option<i64> {
+        Some(do_part2(&as_strs(lines)))
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        if part == 2 {
+            explain_paths(&as_strs(lines), trace);
+        }
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            2 => Some(Example {
+                input: EXAMPLE,
+                answer: 6,
+            }),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day08::{do_part2, gcd, lcm, parse_instructions, Direction, Map, Node};
+    use crate::day08::{do_part2, explain_paths, parse_instructions, Direction, Map, Node};
+    use aoc_core::trace::Trace;
     use std::collections::HashMap;
 
     #[test]
@@ -224,13 +271,31 @@ mod tests {
     }
 
     #[test]
-    fn gcd_success() {
-        assert_eq!(gcd(48, 18), 6);
-        assert_eq!(gcd(48, 47), 1);
-    }
-
-    #[test]
-    fn lcm_success() {
-        assert_eq!(lcm(4, 6), 12);
+    fn explain_paths_success() {
+        let lines = vec![
+            "LR",
+            "",
+            "11A = (11B, XXX)",
+            "11B = (XXX, 11Z)",
+            "11Z = (11B, XXX)",
+            "22A = (22B, XXX)",
+            "22B = (22C, 22C)",
+            "22C = (22Z, 22Z)",
+            "22Z = (22B, 22B)",
+            "XXX = (XXX, XXX)",
+        ];
+        let mut trace = Trace::new(Some(0));
+        explain_paths(&lines, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("start", "11A"),
+                ("step", "11A -Left-> 11B"),
+                ("step", "11B -Right-> 11Z"),
+                ("steps", "2"),
+                ("lcm so far", "2")
+            ]
+        );
     }
 }
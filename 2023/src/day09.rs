@@ -1,8 +1,6 @@
-fn parse_line(line: &str) -> Vec<i64> {
-    line.split_whitespace()
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect()
-}
+use aoc_core::parse::{as_strs, parse_numbers};
+use aoc_core::trace::Trace;
+use aoc_core::{Example, Solution};
 
 fn diff_line(line: &[i64]) -> Vec<i64> {
     std::iter::zip(line[0..].iter(), line[1..].iter())
@@ -23,11 +21,11 @@ fn diff_line_until_zero_tail(line: &[i64]) -> Vec<i64> {
 }
 
 // Applies the `diff_line` operation to line until `line` contains only zeros. Returns the first number in each line.
+#[cfg(test)]
 fn diff_line_until_zero_head(line: &[i64]) -> Vec<i64> {
     let mut result = vec![line[0]];
     let mut line = diff_line(line);
     while line.iter().any(|&x| x != 0) {
-        println!("{:?}", line);
         result.push(line[0]);
         line = diff_line(&line);
     }
@@ -41,7 +39,7 @@ fn extrapolate(line_end: i64, next_line_end: i64) -> i64 {
 
 // Extrapolate the end of the first line.
 fn extrapolate_lines_tail(line_ends: &[i64], next_line_end: i64) -> i64 {
-    if line_ends.len() > 0 {
+    if !line_ends.is_empty() {
         extrapolate_lines_tail(&line_ends[1..], extrapolate(line_ends[0], next_line_end))
     } else {
         next_line_end
@@ -49,8 +47,9 @@ fn extrapolate_lines_tail(line_ends: &[i64], next_line_end: i64) -> i64 {
 }
 
 // Extrapolate the beginning of the first line.
+#[cfg(test)]
 fn extrapolate_lines_head(line_heads: &[i64], next_line_head: i64) -> i64 {
-    if line_heads.len() > 0 {
+    if !line_heads.is_empty() {
         extrapolate_lines_head(&line_heads[1..], line_heads[0] - next_line_head)
     } else {
         next_line_head
@@ -61,39 +60,102 @@ pub fn do_part1(lines: &[&str]) -> i64 {
     lines
         .iter()
         .map(|line| {
-            let line_ends = diff_line_until_zero_tail(parse_line(line).as_slice());
+            let line_ends = diff_line_until_zero_tail(parse_numbers(line).as_slice());
             extrapolate_lines_tail(&line_ends, 0)
         })
-        .fold(0, |acc, x| acc + x)
+        .sum()
 }
 
 pub fn do_part2(lines: &[&str]) -> i64 {
     lines
         .iter()
         .map(|line| {
-            let line_rev: Vec<i64> = parse_line(line).iter().rev().map(|&x| x).collect();
+            let line_rev: Vec<i64> = parse_numbers(line).iter().rev().copied().collect();
             let line_ends = diff_line_until_zero_tail(line_rev.as_slice());
             extrapolate_lines_tail(&line_ends, 0)
         })
-        .fold(0, |acc, x| acc + x)
+        .sum()
+}
+
+// Emits each row of line's difference pyramid followed by the extrapolated value.
+fn explain_pyramids(lines: &[&str], part: u8, trace: &mut Trace) {
+    for (i, line) in lines.iter().enumerate() {
+        if !trace.wants(i) {
+            continue;
+        }
+        let mut row = parse_numbers(line);
+        if part == 2 {
+            row.reverse();
+        }
+        let line_ends = diff_line_until_zero_tail(&row);
+        while row.iter().any(|&x| x != 0) {
+            trace.emit(i, "row", format!("{:?}", row));
+            row = diff_line(&row);
+        }
+        trace.emit(i, "row", format!("{:?}", row));
+        let label = if part == 2 { "previous" } else { "next" };
+        trace.emit(i, label, extrapolate_lines_tail(&line_ends, 0).to_string());
+    }
+}
+
+const EXAMPLE: &str = "\
+0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45
+";
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    fn day(&self) -> &'static str {
+        "day09"
+    }
+
+    fn part1(&self, lines: &[String]) -> Option<i64> {
+        Some(do_part1(&as_strs(lines)))
+    }
+
+    fn part2(&self, lines: &[String]) -> Option<i64> {
+        Some(do_part2(&as_strs(lines)))
+    }
+
+    fn explain(&self, part: u8, lines: &[String], trace: &mut Trace) {
+        explain_pyramids(&as_strs(lines), part, trace);
+    }
+
+    fn example(&self, part: u8) -> Option<Example> {
+        match part {
+            1 => Some(Example {
+                input: EXAMPLE,
+                answer: 114,
+            }),
+            2 => Some(Example {
+                input: EXAMPLE,
+                answer: 2,
+            }),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day09::{
         diff_line, diff_line_until_zero_head, diff_line_until_zero_tail, do_part1, do_part2,
-        extrapolate_lines_head, extrapolate_lines_tail, parse_line,
+        explain_pyramids, extrapolate_lines_head, extrapolate_lines_tail,
     };
+    use aoc_core::parse::parse_numbers;
+    use aoc_core::trace::Trace;
 
     #[test]
     fn parse_line_success() {
-        assert_eq!(parse_line("0 3 6 9 12 15"), vec![0, 3, 6, 9, 12, 15]);
+        assert_eq!(parse_numbers("0 3 6 9 12 15"), vec![0, 3, 6, 9, 12, 15]);
     }
 
     #[test]
     fn diff_line_success() {
         assert_eq!(
-            diff_line(parse_line("0 3 6 9 12 15").as_slice()),
+            diff_line(parse_numbers("0 3 6 9 12 15").as_slice()),
             vec![3, 3, 3, 3, 3]
         );
     }
@@ -101,15 +163,15 @@ mod tests {
     #[test]
     fn diff_line_until_zero_tail_success() {
         assert_eq!(
-            diff_line_until_zero_tail(parse_line("0 3 6 9 12 15").as_slice()),
+            diff_line_until_zero_tail(parse_numbers("0 3 6 9 12 15").as_slice()),
             vec![15, 3, 0]
         );
         assert_eq!(
-            diff_line_until_zero_tail(parse_line("1 3 6 10 15 21").as_slice()),
+            diff_line_until_zero_tail(parse_numbers("1 3 6 10 15 21").as_slice()),
             vec![21, 6, 1, 0]
         );
         assert_eq!(
-            diff_line_until_zero_tail(parse_line("10 13 16 21 30 45").as_slice()),
+            diff_line_until_zero_tail(parse_numbers("10 13 16 21 30 45").as_slice()),
             vec![45, 15, 6, 2, 0]
         );
     }
@@ -117,28 +179,28 @@ mod tests {
     #[test]
     fn diff_line_until_zero_head_success() {
         assert_eq!(
-            diff_line_until_zero_head(parse_line("10 13 16 21 30 45").as_slice()),
+            diff_line_until_zero_head(parse_numbers("10 13 16 21 30 45").as_slice()),
             vec![10, 3, 0, 2, 0]
         );
     }
 
     #[test]
     fn extrapolate_lines_tail_success() {
-        let line_ends1 = diff_line_until_zero_tail(parse_line("0 3 6 9 12 15").as_slice());
+        let line_ends1 = diff_line_until_zero_tail(parse_numbers("0 3 6 9 12 15").as_slice());
         assert_eq!(extrapolate_lines_tail(&line_ends1, 0), 18);
-        let line_ends2 = diff_line_until_zero_tail(parse_line("1 3 6 10 15 21").as_slice());
+        let line_ends2 = diff_line_until_zero_tail(parse_numbers("1 3 6 10 15 21").as_slice());
         assert_eq!(extrapolate_lines_tail(&line_ends2, 0), 28);
-        let line_ends3 = diff_line_until_zero_tail(parse_line("10 13 16 21 30 45").as_slice());
+        let line_ends3 = diff_line_until_zero_tail(parse_numbers("10 13 16 21 30 45").as_slice());
         assert_eq!(extrapolate_lines_tail(&line_ends3, 0), 68);
     }
 
     #[test]
     fn extrapolate_lines_head_success() {
-        let line_ends1 = diff_line_until_zero_head(parse_line("0 3 6 9 12 15").as_slice());
+        let line_ends1 = diff_line_until_zero_head(parse_numbers("0 3 6 9 12 15").as_slice());
         assert_eq!(extrapolate_lines_head(&line_ends1, 0), -3);
-        let line_ends2 = diff_line_until_zero_head(parse_line("1 3 6 10 15 21").as_slice());
+        let line_ends2 = diff_line_until_zero_head(parse_numbers("1 3 6 10 15 21").as_slice());
         assert_eq!(extrapolate_lines_head(&line_ends2, 0), 0);
-        let line_ends3 = diff_line_until_zero_head(parse_line("10 13 16 21 30 45").as_slice());
+        let line_ends3 = diff_line_until_zero_head(parse_numbers("10 13 16 21 30 45").as_slice());
         assert_eq!(extrapolate_lines_head(&line_ends3, 0), 5);
     }
 
@@ -153,4 +215,25 @@ mod tests {
         let lines = vec!["0 3 6 9 12 15", "1 3 6 10 15 21", "10 13 16 21 30 45"];
         assert_eq!(do_part2(lines.as_slice()), 2);
     }
+
+    #[test]
+    fn explain_pyramids_success() {
+        let lines = vec!["0 3 6 9 12 15", "1 3 6 10 15 21", "10 13 16 21 30 45"];
+        let mut trace = Trace::new(Some(0));
+        explain_pyramids(lines.as_slice(), 1, &mut trace);
+        let events = trace.pairs();
+        assert_eq!(
+            events,
+            vec![
+                ("row", "[0, 3, 6, 9, 12, 15]"),
+                ("row", "[3, 3, 3, 3, 3]"),
+                ("row", "[0, 0, 0, 0]"),
+                ("next", "18")
+            ]
+        );
+
+        let mut trace = Trace::new(Some(2));
+        explain_pyramids(lines.as_slice(), 2, &mut trace);
+        assert_eq!(trace.events().last().unwrap().detail, "5");
+    }
 }
@@ -0,0 +1,45 @@
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+
+use aoc_core::Solution;
+
+pub const SOLUTIONS: [&dyn Solution; 9] = [
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::SOLUTIONS;
+
+    #[test]
+    fn examples_pass() {
+        for solution in SOLUTIONS {
+            for part in 1..=2 {
+                if let Some(example) = solution.example(part) {
+                    assert_eq!(
+                        solution.solve(part, &example.lines()),
+                        Some(Ok(example.answer)),
+                        "{} part {}",
+                        solution.day(),
+                        part
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_BYTES: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BASELINE_BYTES: AtomicUsize = AtomicUsize::new(0);
+// Set once something we can't account for is allocating in the background.
+static UNRELIABLE: AtomicBool = AtomicBool::new(false);
+
+// Wraps the system allocator and counts every allocation made through it. Only installed as the
+// global allocator when the `alloc-stats` feature is enabled.
+pub struct CountingAllocator;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn record_alloc(size: usize) {
+    let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+    TOTAL_BYTES.fetch_add(size, Ordering::Relaxed);
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn record_dealloc(size: usize) {
+    CURRENT_BYTES.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_dealloc(layout.size());
+    }
+
+    // A realloc counts as freeing the old block and allocating the new one.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct AllocStats {
+    // Highest number of bytes live at once, above what was live when the counters were reset.
+    pub peak_bytes: usize,
+    pub total_bytes: usize,
+    pub allocations: usize,
+}
+
+// Starts a new measurement. Memory that is already allocated doesn't count towards the peak.
+pub fn reset() {
+    let current = CURRENT_BYTES.load(Ordering::Relaxed);
+    BASELINE_BYTES.store(current, Ordering::Relaxed);
+    PEAK_BYTES.store(current, Ordering::Relaxed);
+    TOTAL_BYTES.store(0, Ordering::Relaxed);
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+}
+
+pub fn snapshot() -> AllocStats {
+    let baseline = BASELINE_BYTES.load(Ordering::Relaxed);
+    AllocStats {
+        peak_bytes: PEAK_BYTES.load(Ordering::Relaxed).saturating_sub(baseline),
+        total_bytes: TOTAL_BYTES.load(Ordering::Relaxed),
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+    }
+}
+
+// Stops reporting allocations for the rest of the run. Used when a thread that was given up on
+// keeps running, since whatever it allocates would be counted towards every later measurement.
+pub fn mark_unreliable() {
+    UNRELIABLE.store(true, Ordering::Relaxed);
+}
+
+// Runs f and returns what it allocated, or None when the counting allocator isn't installed or
+// the counts have been marked unreliable.
+pub fn measure<R, F: FnOnce() -> R>(f: F) -> (R, Option<AllocStats>) {
+    if cfg!(feature = "alloc-stats") {
+        reset();
+        let result = f();
+        let stats = snapshot();
+        (
+            result,
+            (!UNRELIABLE.load(Ordering::Relaxed)).then_some(stats),
+        )
+    } else {
+        (f(), None)
+    }
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+impl std::fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "peak {}, total {} in {} allocations",
+            format_bytes(self.peak_bytes),
+            format_bytes(self.total_bytes),
+            self.allocations
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alloc_stats::{format_bytes, AllocStats};
+
+    #[test]
+    fn format_bytes_success() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn alloc_stats_display() {
+        let stats = AllocStats {
+            peak_bytes: 2048,
+            total_bytes: 4096,
+            allocations: 12,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "peak 2.0 KiB, total 4.0 KiB in 12 allocations"
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use crate::runner::Options;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const CONFIG_FILE: &str = "aoc.toml";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    // One JSON object per answer.
+    Json,
+    // A header row, then one row per answer.
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Result<OutputFormat, String> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!("Invalid output format {}", s)),
+        }
+    }
+}
+
+// Settings from aoc.toml, with command-line flags applied on top.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    // Where `dayNN.input` files are read from.
+    pub input_dir: PathBuf,
+    // Runs both parts when None.
+    pub default_part: Option<u8>,
+    pub output_format: OutputFormat,
+    // How long a single part may run before it's abandoned.
+    pub timeout: Option<Duration>,
+    // How many days the report solves at once.
+    pub threads: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            input_dir: PathBuf::from("."),
+            default_part: None,
+            output_format: OutputFormat::Text,
+            timeout: None,
+            threads: 1,
+        }
+    }
+}
+
+// Parses a quoted string or an integer. Only the small subset of TOML the config needs.
+fn parse_value(value: &str) -> Result<Value, String> {
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut result = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    let trailing = chars.as_str().trim();
+                    if !trailing.is_empty() && !trailing.starts_with('#') {
+                        return Err(format!("Unexpected {} after string", trailing));
+                    }
+                    return Ok(Value::String(result));
+                }
+                '\\' => match chars.next() {
+                    Some('"') => result.push('"'),
+                    Some('\\') => result.push('\\'),
+                    Some('n') => result.push('\n'),
+                    Some('t') => result.push('\t'),
+                    other => return Err(format!("Invalid escape {:?}", other)),
+                },
+                _ => result.push(c),
+            }
+        }
+        Err(String::from("Unterminated string"))
+    } else {
+        let value = value.split('#').next().unwrap().trim();
+        value
+            .parse::<u64>()
+            .map(Value::Integer)
+            .map_err(|_| format!("Invalid value {}", value))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Value {
+    String(String),
+    Integer(u64),
+}
+
+impl Value {
+    fn string(self, key: &str) -> Result<String, String> {
+        match self {
+            Value::String(s) => Ok(s),
+            Value::Integer(_) => Err(format!("{} must be a string", key)),
+        }
+    }
+
+    fn integer(self, key: &str) -> Result<u64, String> {
+        match self {
+            Value::Integer(n) => Ok(n),
+            Value::String(_) => Err(format!("{} must be an integer", key)),
+        }
+    }
+}
+
+pub fn parse_part(part: u64) -> Result<u8, String> {
+    match part {
+        1 | 2 => Ok(part as u8),
+        _ => Err(format!("Invalid part {}", part)),
+    }
+}
+
+pub fn parse_threads(threads: u64) -> Result<usize, String> {
+    if threads == 0 {
+        Err(String::from("threads must be at least 1"))
+    } else {
+        Ok(threads as usize)
+    }
+}
+
+impl Config {
+    // Parses the contents of a config file. Relative paths are resolved against `base_dir`, the
+    // directory the file is in.
+    pub fn parse(text: &str, base_dir: &Path) -> Result<Config, String> {
+        let mut config = Config::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |err: String| format!("{}:{}: {}", CONFIG_FILE, i + 1, err);
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(String::from("Expected key = value")))?;
+            let key = key.trim();
+            let value = parse_value(value.trim()).map_err(error)?;
+            match key {
+                "input_dir" => config.input_dir = base_dir.join(value.string(key).map_err(error)?),
+                "default_part" => {
+                    config.default_part =
+                        Some(parse_part(value.integer(key).map_err(error)?).map_err(error)?)
+                }
+                "output_format" => {
+                    config.output_format =
+                        OutputFormat::parse(&value.string(key).map_err(error)?).map_err(error)?
+                }
+                "timeout_secs" => {
+                    config.timeout = Some(Duration::from_secs(value.integer(key).map_err(error)?))
+                }
+                "threads" => {
+                    config.threads =
+                        parse_threads(value.integer(key).map_err(error)?).map_err(error)?
+                }
+                _ => return Err(error(format!("Unknown key {}", key))),
+            }
+        }
+        Ok(config)
+    }
+
+    // Looks for aoc.toml in the current directory and each of its parents, falling back to the
+    // defaults if there isn't one.
+    pub fn load() -> Result<Config, String> {
+        let current_dir = std::env::current_dir().map_err(|err| err.to_string())?;
+        match find(&current_dir) {
+            Some(path) => {
+                let text = fs::read_to_string(&path)
+                    .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+                Config::parse(&text, path.parent().unwrap())
+            }
+            None => Ok(Config::default()),
+        }
+    }
+
+    // Applies the command-line flags, which take precedence over the file.
+    pub fn override_with(&mut self, options: &Options) {
+        if let Some(input_dir) = &options.input_dir {
+            self.input_dir = PathBuf::from(input_dir);
+        }
+        if options.part.is_some() {
+            self.default_part = options.part;
+        }
+        if let Some(output_format) = options.output_format {
+            self.output_format = output_format;
+        }
+        if options.timeout.is_some() {
+            self.timeout = options.timeout;
+        }
+        if let Some(threads) = options.threads {
+            self.threads = threads;
+        }
+    }
+
+    pub fn input_path(&self, day: &str) -> PathBuf {
+        self.input_dir.join(format!("{}.input", day))
+    }
+}
+
+pub fn find(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{find, Config, OutputFormat, CONFIG_FILE};
+    use crate::runner::Options;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    #[test]
+    fn parse_success() {
+        let text = "
+# Where the puzzle inputs live.
+input_dir = \"inputs\"
+default_part = 2
+output_format = \"json\" # for scripts
+timeout_secs = 30
+threads = 4
+";
+        assert_eq!(
+            Config::parse(text, Path::new("/aoc")),
+            Ok(Config {
+                input_dir: PathBuf::from("/aoc/inputs"),
+                default_part: Some(2),
+                output_format: OutputFormat::Json,
+                timeout: Some(Duration::from_secs(30)),
+                threads: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_empty() {
+        assert_eq!(Config::parse("", Path::new("/aoc")), Ok(Config::default()));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let base_dir = Path::new("/aoc");
+        assert_eq!(
+            Config::parse("\nbogus = 1", base_dir),
+            Err(String::from("aoc.toml:2: Unknown key bogus"))
+        );
+        assert!(Config::parse("default_part = 3", base_dir).is_err());
+        assert!(Config::parse("default_part = \"2\"", base_dir).is_err());
+        assert!(Config::parse("threads = 0", base_dir).is_err());
+        assert!(Config::parse("input_dir = \"inputs", base_dir).is_err());
+        assert!(Config::parse("input_dir", base_dir).is_err());
+        assert!(Config::parse("output_format = \"yaml\"", base_dir).is_err());
+    }
+
+    #[test]
+    fn override_with_flags() {
+        let mut config = Config::parse("default_part = 2\nthreads = 4", Path::new("/aoc")).unwrap();
+        config.override_with(&Options {
+            part: Some(1),
+            input_dir: Some(String::from("elsewhere")),
+            ..Options::default()
+        });
+        assert_eq!(config.default_part, Some(1));
+        assert_eq!(config.threads, 4);
+        assert_eq!(
+            config.input_path("day01"),
+            PathBuf::from("elsewhere/day01.input")
+        );
+    }
+
+    #[test]
+    fn find_in_parent() {
+        let root = std::env::temp_dir().join(format!("aoc-config-{}", std::process::id()));
+        let nested = root.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(CONFIG_FILE), "").unwrap();
+        assert_eq!(find(&nested), Some(root.join(CONFIG_FILE)));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
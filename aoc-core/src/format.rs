@@ -0,0 +1,102 @@
+// Lines the cells up in columns two spaces apart, with the header as the first row.
+pub fn text_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(header[i].chars().count()))
+                .max()
+                .unwrap()
+        })
+        .collect();
+    let mut out = String::new();
+    let header: Vec<String> = header.iter().map(|cell| String::from(*cell)).collect();
+    for row in std::iter::once(&header).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        out += cells.join("  ").trim_end();
+        out += "\n";
+    }
+    out
+}
+
+// A JSON string literal.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out + "\""
+}
+
+// Quotes a CSV field if it needs it.
+pub fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+// A DOT quoted string. Graphviz reads escapes like \n and \N in labels, so backslashes are
+// escaped as well as quotes.
+pub fn dot_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            c => out.push(c),
+        }
+    }
+    out + "\""
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::{dot_string, escape_csv, json_string, text_table};
+
+    #[test]
+    fn escape_csv_success() {
+        assert_eq!(escape_csv("plain"), "plain");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn text_table_success() {
+        let rows = vec![
+            vec![String::from("1"), String::from("long cell")],
+            vec![String::from("22"), String::from("x")],
+        ];
+        assert_eq!(
+            text_table(&["id", "value"], &rows),
+            "id  value\n1   long cell\n22  x\n"
+        );
+    }
+
+    #[test]
+    fn json_string_success() {
+        assert_eq!(json_string("red"), "\"red\"");
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn dot_string_success() {
+        assert_eq!(dot_string("*"), "\"*\"");
+        assert_eq!(dot_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+    }
+}
@@ -0,0 +1,84 @@
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    // Builds a grid from rows, which must all be the same length. Returns None for ragged rows.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.height && col < self.width {
+            self.cells.get(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    // The up to eight cells surrounding (row, col) that are inside the grid.
+    pub fn neighbors(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (height, width) = (self.height, self.width);
+        let rows = row.saturating_sub(1)..(row + 2).min(height);
+        rows.flat_map(move |r| (col.saturating_sub(1)..(col + 2).min(width)).map(move |c| (r, c)))
+            .filter(move |&pos| pos != (row, col))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+
+    fn chars(lines: &[&str]) -> Option<Grid<char>> {
+        Grid::from_rows(lines.iter().map(|line| line.chars().collect()).collect())
+    }
+
+    #[test]
+    fn from_rows_success() {
+        let grid = chars(&["ab", "cd", "ef"]).unwrap();
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid.get(2, 1), Some(&'f'));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+    }
+
+    #[test]
+    fn from_rows_ragged() {
+        assert_eq!(chars(&["ab", "c"]), None);
+    }
+
+    #[test]
+    fn neighbors_success() {
+        let grid = chars(&["abc", "def", "ghi"]).unwrap();
+        assert_eq!(grid.neighbors(1, 1).count(), 8);
+        assert_eq!(
+            grid.neighbors(0, 0).collect::<Vec<(usize, usize)>>(),
+            vec![(0, 1), (1, 0), (1, 1)]
+        );
+        assert_eq!(
+            grid.neighbors(2, 2).collect::<Vec<(usize, usize)>>(),
+            vec![(1, 1), (1, 2), (2, 1)]
+        );
+    }
+}
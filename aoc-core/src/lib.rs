@@ -0,0 +1,11 @@
+pub mod alloc_stats;
+pub mod config;
+pub mod format;
+pub mod grid;
+pub mod math;
+pub mod parse;
+pub mod report;
+pub mod runner;
+pub mod trace;
+
+pub use runner::{Example, Solution, View};
@@ -0,0 +1,32 @@
+pub fn gcd(x: i64, y: i64) -> i64 {
+    let mut a = x;
+    let mut b = y;
+    while a != b {
+        if a > b {
+            a -= b;
+        } else {
+            b -= a;
+        }
+    }
+    a
+}
+
+pub fn lcm(x: i64, y: i64) -> i64 {
+    (x * y).abs() / gcd(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{gcd, lcm};
+
+    #[test]
+    fn gcd_success() {
+        assert_eq!(gcd(48, 18), 6);
+        assert_eq!(gcd(48, 47), 1);
+    }
+
+    #[test]
+    fn lcm_success() {
+        assert_eq!(lcm(4, 6), 12);
+    }
+}
@@ -0,0 +1,41 @@
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::path::Path;
+
+pub fn read_lines(file_path: &Path) -> io::Result<io::Lines<io::BufReader<File>>> {
+    let file = File::open(file_path)?;
+    Ok(io::BufReader::new(file).lines())
+}
+
+// Reads the whole file into memory, one `String` per line.
+pub fn read_input(file_path: &Path) -> io::Result<Vec<String>> {
+    read_lines(file_path)?.collect()
+}
+
+pub fn as_strs(lines: &[String]) -> Vec<&str> {
+    lines.iter().map(String::as_str).collect()
+}
+
+// Parse whitespace-separated integers from s or panic.
+pub fn parse_numbers(s: &str) -> Vec<i64> {
+    s.split_whitespace()
+        .map(|n| n.parse::<i64>().unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{as_strs, parse_numbers};
+
+    #[test]
+    fn as_strs_success() {
+        let lines = vec![String::from("ab"), String::from("cd")];
+        assert_eq!(as_strs(&lines), vec!["ab", "cd"]);
+    }
+
+    #[test]
+    fn parse_numbers_success() {
+        assert_eq!(parse_numbers(" 1 -21  53"), vec![1, -21, 53]);
+        assert_eq!(parse_numbers(""), Vec::<i64>::new());
+    }
+}
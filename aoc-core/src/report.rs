@@ -0,0 +1,356 @@
+use crate::alloc_stats::format_bytes;
+use crate::config::Config;
+use crate::runner::{
+    read_day_input, run_part_with_timeout, Options, Outcome, PartResult, Solution,
+};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ExampleStatus {
+    Passed,
+    Failed { expected: i64, actual: i64 },
+    // The day doesn't provide an example for this part.
+    Missing,
+    Panicked,
+    // The part rejected its own example.
+    Error(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PartReport {
+    pub day: &'static str,
+    pub part: u8,
+    // Why there's no answer, like a missing input or a timeout.
+    pub result: Result<PartResult, String>,
+    pub example: ExampleStatus,
+}
+
+// Runs the part on its example. Returns None if the part isn't solved.
+fn check_example(solution: &dyn Solution, part: u8) -> Option<ExampleStatus> {
+    let Some(example) = solution.example(part) else {
+        return Some(ExampleStatus::Missing);
+    };
+    let lines = example.lines();
+    let Ok(actual) = panic::catch_unwind(AssertUnwindSafe(|| solution.solve(part, &lines))) else {
+        return Some(ExampleStatus::Panicked);
+    };
+    let actual = match actual? {
+        Ok(actual) => actual,
+        Err(err) => return Some(ExampleStatus::Error(err)),
+    };
+    if actual == example.answer {
+        Some(ExampleStatus::Passed)
+    } else {
+        Some(ExampleStatus::Failed {
+            expected: example.answer,
+            actual,
+        })
+    }
+}
+
+fn build_day(solution: &'static dyn Solution, config: &Config) -> Vec<PartReport> {
+    let lines = read_day_input(solution, config).ok().map(Arc::new);
+    let mut reports = Vec::new();
+    for part in 1..=2 {
+        let Some(example) = check_example(solution, part) else {
+            continue;
+        };
+        let result = match &lines {
+            Some(lines) => match run_part_with_timeout(solution, part, lines, config.timeout) {
+                Outcome::Solved(result) => Ok(result),
+                Outcome::TimedOut => Err(String::from("timed out")),
+                Outcome::Panicked => Err(String::from("panicked")),
+                Outcome::Failed(err) => Err(err),
+                Outcome::Unsolved => continue,
+            },
+            None => Err(String::from("no input")),
+        };
+        reports.push(PartReport {
+            day: solution.day(),
+            part,
+            result,
+            example,
+        });
+    }
+    reports
+}
+
+// Solves every part of every day, skipping parts that aren't solved yet. Days are spread over
+// `config.threads` threads. Allocation counts are global, so they're dropped when more than one
+// day runs at a time.
+pub fn build(solutions: &'static [&'static dyn Solution], config: &Config) -> Vec<PartReport> {
+    let next_day = AtomicUsize::new(0);
+    let days: Mutex<Vec<(usize, Vec<PartReport>)>> = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..config.threads.min(solutions.len()) {
+            scope.spawn(|| loop {
+                let i = next_day.fetch_add(1, Ordering::Relaxed);
+                let Some(&solution) = solutions.get(i) else {
+                    break;
+                };
+                let reports = build_day(solution, config);
+                days.lock().unwrap().push((i, reports));
+            });
+        }
+    });
+    let mut days = days.into_inner().unwrap();
+    days.sort_by_key(|(i, _)| *i);
+    let mut reports: Vec<PartReport> = days.into_iter().flat_map(|(_, reports)| reports).collect();
+    if config.threads > 1 {
+        for result in reports
+            .iter_mut()
+            .filter_map(|report| report.result.as_mut().ok())
+        {
+            result.alloc = None;
+        }
+    }
+    reports
+}
+
+const HEADER: [&str; 6] = ["Day", "Part", "Answer", "Time", "Peak memory", "Example"];
+
+fn cells(report: &PartReport, redact: bool) -> [String; 6] {
+    let (answer, time, memory) = match &report.result {
+        Ok(result) => (
+            if redact {
+                String::from("redacted")
+            } else {
+                result.answer.to_string()
+            },
+            format!("{:.2?}", result.elapsed),
+            result
+                .alloc
+                .map_or(String::from("-"), |alloc| format_bytes(alloc.peak_bytes)),
+        ),
+        Err(reason) => (reason.clone(), String::from("-"), String::from("-")),
+    };
+    let example = match &report.example {
+        ExampleStatus::Passed => String::from("passed"),
+        ExampleStatus::Failed { expected, actual } => {
+            format!("failed (expected {}, got {})", expected, actual)
+        }
+        ExampleStatus::Missing => String::from("-"),
+        ExampleStatus::Panicked => String::from("panicked"),
+        ExampleStatus::Error(err) => format!("error ({})", err),
+    };
+    [
+        String::from(report.day),
+        report.part.to_string(),
+        answer,
+        time,
+        memory,
+        example,
+    ]
+}
+
+pub fn markdown(reports: &[PartReport], redact: bool) -> String {
+    let mut out = format!("| {} |\n", HEADER.join(" | "));
+    out += &format!("|{}\n", "---|".repeat(HEADER.len()));
+    for report in reports {
+        // Error messages come from the input, so they might hold a '|'.
+        let cells = cells(report, redact).map(|cell| cell.replace('|', "\\|"));
+        out += &format!("| {} |\n", cells.join(" | "));
+    }
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn html(reports: &[PartReport], redact: bool) -> String {
+    let mut rows = String::new();
+    for report in reports {
+        rows += "<tr>";
+        for cell in cells(report, redact) {
+            rows += &format!("<td>{}</td>", escape_html(&cell));
+        }
+        rows += "</tr>\n";
+    }
+    let header: String = HEADER
+        .iter()
+        .map(|cell| format!("<th>{}</th>", cell))
+        .collect();
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Advent of Code progress</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 0.25em 0.75em; text-align: left; }}
+</style>
+</head>
+<body>
+<table>
+<tr>{}</tr>
+{}</table>
+</body>
+</html>
+",
+        header, rows
+    )
+}
+
+// Writes report.md and report.html for every day.
+pub fn run(solutions: &'static [&'static dyn Solution], config: &Config, options: &Options) {
+    let reports = build(solutions, config);
+    let out_dir = Path::new(options.out_dir.as_deref().unwrap_or("."));
+    let outputs = [
+        ("report.md", markdown(&reports, options.redact)),
+        ("report.html", html(&reports, options.redact)),
+    ];
+    for (name, contents) in outputs {
+        let path = out_dir.join(name);
+        match fs::write(&path, contents) {
+            Ok(()) => println!("Wrote {}", path.display()),
+            Err(err) => println!("Failed to write {}: {}", path.display(), err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::report::{build, check_example, html, markdown, ExampleStatus, PartReport};
+    use crate::runner::{Example, PartResult, Solution};
+    use std::time::Duration;
+
+    struct CountLines;
+
+    impl Solution for CountLines {
+        fn day(&self) -> &'static str {
+            "count"
+        }
+
+        fn part1(&self, lines: &[String]) -> Option<i64> {
+            Some(lines.len() as i64)
+        }
+
+        fn example(&self, part: u8) -> Option<Example> {
+            match part {
+                1 => Some(Example {
+                    input: "a\nb\n",
+                    answer: 2,
+                }),
+                2 => Some(Example {
+                    input: "a\n",
+                    answer: 1,
+                }),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn check_example_success() {
+        assert_eq!(check_example(&CountLines, 1), Some(ExampleStatus::Passed));
+        // Part 2 has an example but isn't solved.
+        assert_eq!(check_example(&CountLines, 2), None);
+        assert_eq!(check_example(&CountLines, 3), Some(ExampleStatus::Missing));
+    }
+
+    struct Panics;
+
+    impl Solution for Panics {
+        fn day(&self) -> &'static str {
+            "panics"
+        }
+
+        fn part1(&self, _lines: &[String]) -> Option<i64> {
+            panic!("bad example")
+        }
+
+        fn example(&self, _part: u8) -> Option<Example> {
+            Some(Example {
+                input: "a\n",
+                answer: 1,
+            })
+        }
+    }
+
+    #[test]
+    fn check_example_panicked() {
+        assert_eq!(check_example(&Panics, 1), Some(ExampleStatus::Panicked));
+        let config = Config {
+            input_dir: std::env::temp_dir().join("aoc-report-missing"),
+            ..Config::default()
+        };
+        let reports = build(&[&Panics, &CountLines], &config);
+        assert_eq!(reports[0].example, ExampleStatus::Panicked);
+        assert_eq!(reports[1].example, ExampleStatus::Passed);
+    }
+
+    #[test]
+    fn build_without_input() {
+        let config = Config {
+            input_dir: std::env::temp_dir().join("aoc-report-missing"),
+            threads: 2,
+            ..Config::default()
+        };
+        let reports = build(&[&CountLines, &CountLines], &config);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].result, Err(String::from("no input")));
+        assert_eq!(reports[0].example, ExampleStatus::Passed);
+    }
+
+    fn reports() -> Vec<PartReport> {
+        vec![
+            PartReport {
+                day: "day01",
+                part: 1,
+                result: Ok(PartResult {
+                    part: 1,
+                    answer: 142,
+                    elapsed: Duration::from_micros(1500),
+                    alloc: None,
+                }),
+                example: ExampleStatus::Passed,
+            },
+            PartReport {
+                day: "day01",
+                part: 2,
+                result: Err(String::from("no input")),
+                example: ExampleStatus::Failed {
+                    expected: 281,
+                    actual: 280,
+                },
+            },
+        ]
+    }
+
+    #[test]
+    fn markdown_success() {
+        assert_eq!(
+            markdown(&reports(), false),
+            "| Day | Part | Answer | Time | Peak memory | Example |
+|---|---|---|---|---|---|
+| day01 | 1 | 142 | 1.50ms | - | passed |
+| day01 | 2 | no input | - | - | failed (expected 281, got 280) |
+"
+        );
+    }
+
+    #[test]
+    fn markdown_redacted() {
+        assert!(markdown(&reports(), true).contains("| day01 | 1 | redacted | 1.50ms |"));
+    }
+
+    #[test]
+    fn html_success() {
+        let page = html(&reports(), false);
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<tr><td>day01</td><td>1</td><td>142</td>"));
+        assert!(page.contains("<th>Peak memory</th>"));
+    }
+}
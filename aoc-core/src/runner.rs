@@ -0,0 +1,807 @@
+use crate::alloc_stats::{self, AllocStats};
+use crate::config::{parse_part, parse_threads, Config, OutputFormat};
+use crate::format::{escape_csv, json_string};
+use crate::parse::read_input;
+use crate::report;
+use crate::trace::Trace;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A puzzle's worked example and the answer it should give.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Example {
+    pub input: &'static str,
+    pub answer: i64,
+}
+
+impl Example {
+    pub fn lines(&self) -> Vec<String> {
+        self.input.lines().map(String::from).collect()
+    }
+}
+
+// A day-specific way of looking at the input, picked with --view.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct View<'a> {
+    pub name: &'a str,
+    pub part: Option<u8>,
+    pub format: OutputFormat,
+    // From --set key=value.
+    pub params: &'a [(String, String)],
+}
+
+impl View<'_> {
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// One day's puzzle. Parts that haven't been solved yet return None.
+pub trait Solution: Sync {
+    // The name used on the command line and for the input file, e.g. "day01".
+    fn day(&self) -> &'static str;
+
+    fn part1(&self, _lines: &[String]) -> Option<i64> {
+        None
+    }
+
+    fn part2(&self, _lines: &[String]) -> Option<i64> {
+        None
+    }
+
+    // Like part1 and part2, for days whose input can be malformed. Err says what's wrong with
+    // it, like "line 3: no digit", instead of the part panicking.
+    fn try_part1(&self, lines: &[String]) -> Option<Result<i64, String>> {
+        self.part1(lines).map(Ok)
+    }
+
+    fn try_part2(&self, lines: &[String]) -> Option<Result<i64, String>> {
+        self.part2(lines).map(Ok)
+    }
+
+    // Emits the intermediate steps behind a part's answer into `trace`. Days without an
+    // explanation leave it empty.
+    fn explain(&self, _part: u8, _lines: &[String], _trace: &mut Trace) {}
+
+    // Renders a view of the input. Returns None if the day doesn't have a view by that name.
+    fn view(&self, _view: &View, _lines: &[String]) -> Option<Result<String, String>> {
+        None
+    }
+
+    // The example from the puzzle text for a part, if there is one.
+    fn example(&self, _part: u8) -> Option<Example> {
+        None
+    }
+
+    // Solves a part straight from the input, without reading it all into memory first. Days that
+    // can't stream a part return None.
+    fn solve_stream(&self, _part: u8, _reader: &mut dyn BufRead) -> Option<io::Result<i64>> {
+        None
+    }
+
+    fn solve(&self, part: u8, lines: &[String]) -> Option<Result<i64, String>> {
+        match part {
+            1 => self.try_part1(lines),
+            2 => self.try_part2(lines),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PartResult {
+    pub part: u8,
+    pub answer: i64,
+    pub elapsed: Duration,
+    // Only available when built with the `alloc-stats` feature.
+    pub alloc: Option<AllocStats>,
+}
+
+impl PartResult {
+    pub fn to_json(&self, day: &str) -> String {
+        format!(
+            "{{\"day\": \"{}\", \"part\": {}, \"answer\": {}, \"elapsed_ns\": {}, \"peak_bytes\": {}}}",
+            day,
+            self.part,
+            self.answer,
+            self.elapsed.as_nanos(),
+            self.alloc
+                .map_or(String::from("null"), |alloc| alloc.peak_bytes.to_string())
+        )
+    }
+}
+
+impl std::fmt::Display for PartResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "part {}: {} ({:?}", self.part, self.answer, self.elapsed)?;
+        if let Some(alloc) = self.alloc {
+            write!(f, ", {}", alloc)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// Solves one part, timing it and counting its allocations. Returns None if the part isn't solved,
+// and an error if the input is malformed.
+pub fn run_part(
+    solution: &dyn Solution,
+    part: u8,
+    lines: &[String],
+) -> Option<Result<PartResult, String>> {
+    let ((answer, elapsed), alloc) = alloc_stats::measure(|| {
+        let start = Instant::now();
+        let answer = solution.solve(part, lines);
+        (answer, start.elapsed())
+    });
+    answer.map(|answer| {
+        answer.map(|answer| PartResult {
+            part,
+            answer,
+            elapsed,
+            alloc,
+        })
+    })
+}
+
+// Like run_part, but reads the input from `path` as it solves. Returns None if the day can't
+// stream the part.
+pub fn run_part_streaming(
+    solution: &dyn Solution,
+    part: u8,
+    path: &Path,
+) -> Option<io::Result<PartResult>> {
+    let mut reader = match File::open(path) {
+        Ok(file) => BufReader::new(file),
+        Err(err) => return Some(Err(err)),
+    };
+    let ((answer, elapsed), alloc) = alloc_stats::measure(|| {
+        let start = Instant::now();
+        let answer = solution.solve_stream(part, &mut reader);
+        (answer, start.elapsed())
+    });
+    answer.map(|answer| {
+        answer.map(|answer| PartResult {
+            part,
+            answer,
+            elapsed,
+            alloc,
+        })
+    })
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Outcome {
+    Solved(PartResult),
+    Unsolved,
+    TimedOut,
+    Panicked,
+    Failed(String),
+}
+
+// Runs `f` on its own thread and gives up after `timeout`, leaving the thread running in the
+// background. That thread's allocations can't be told apart from anyone else's, so allocation
+// stats are switched off for the rest of the run. Without a timeout `f` runs here. Either way a
+// panic is reported as Outcome::Panicked rather than ending the run.
+fn with_timeout(
+    f: impl FnOnce() -> Outcome + Send + 'static,
+    timeout: Option<Duration>,
+) -> Outcome {
+    let Some(timeout) = timeout else {
+        return panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(Outcome::Panicked);
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone if we already timed out.
+        let _ = sender.send(f());
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(outcome) => outcome,
+        // The sender was dropped without sending, so the part panicked.
+        Err(RecvTimeoutError::Disconnected) => Outcome::Panicked,
+        Err(RecvTimeoutError::Timeout) => {
+            alloc_stats::mark_unreliable();
+            Outcome::TimedOut
+        }
+    }
+}
+
+// Like run_part, but gives up after `timeout`.
+pub fn run_part_with_timeout(
+    solution: &'static dyn Solution,
+    part: u8,
+    lines: &Arc<Vec<String>>,
+    timeout: Option<Duration>,
+) -> Outcome {
+    let lines = Arc::clone(lines);
+    with_timeout(
+        move || match run_part(solution, part, &lines) {
+            Some(Ok(result)) => Outcome::Solved(result),
+            Some(Err(err)) => Outcome::Failed(err),
+            None => Outcome::Unsolved,
+        },
+        timeout,
+    )
+}
+
+// Like run_part_streaming, but gives up after `timeout`. Parts that can't stream fail.
+pub fn run_part_streaming_with_timeout(
+    solution: &'static dyn Solution,
+    part: u8,
+    path: PathBuf,
+    timeout: Option<Duration>,
+) -> Outcome {
+    with_timeout(
+        move || match run_part_streaming(solution, part, &path) {
+            Some(Ok(result)) => Outcome::Solved(result),
+            Some(Err(err)) => Outcome::Failed(err.to_string()),
+            None => Outcome::Failed(String::from("can't stream")),
+        },
+        timeout,
+    )
+}
+
+pub fn run_parts(solution: &dyn Solution, lines: &[String]) -> Vec<Result<PartResult, String>> {
+    (1..=2)
+        .filter_map(|part| run_part(solution, part, lines))
+        .collect()
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    // A day like "day01", or "report".
+    pub command: String,
+    pub part: Option<u8>,
+    // Some(None) explains every item, Some(Some(n)) only item n.
+    pub explain: Option<Option<usize>>,
+    // Hides the answers in the report.
+    pub redact: bool,
+    // Where the report is written, the current directory by default.
+    pub out_dir: Option<String>,
+    // These override the matching settings in aoc.toml.
+    pub input_dir: Option<String>,
+    pub output_format: Option<OutputFormat>,
+    pub timeout: Option<Duration>,
+    pub threads: Option<usize>,
+    // A day-specific view to print instead of the answers.
+    pub view: Option<String>,
+    pub params: Vec<(String, String)>,
+    // Solves straight from the input file instead of reading it into memory first.
+    pub stream: bool,
+}
+
+const USAGE: &str = "usage: <day> [--part <1|2>] [--explain [<item>]] [--stream] [options]
+       <day> --view <name> [--set <key>=<value>]... [options]
+       report [--redact] [--out <dir>] [options]
+options: --input-dir <dir> --format <text|json|csv> --timeout <secs> --threads <n>";
+
+fn parse_u64(flag: &str, value: Option<&String>) -> Result<u64, String> {
+    let value = value.ok_or(format!("{} needs a value", flag))?;
+    value
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid {} {}", flag, value))
+}
+
+// Parses the arguments after the binary name.
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part" => options.part = Some(parse_part(parse_u64(arg, args.next())?)?),
+            "--explain" => {
+                let item = args.next_if(|item| item.parse::<usize>().is_ok());
+                options.explain = Some(item.map(|item| item.parse::<usize>().unwrap()));
+            }
+            "--redact" => options.redact = true,
+            "--stream" => options.stream = true,
+            "--out" => {
+                let out_dir = args.next().ok_or("--out needs a value")?;
+                options.out_dir = Some(out_dir.clone());
+            }
+            "--input-dir" => {
+                let input_dir = args.next().ok_or("--input-dir needs a value")?;
+                options.input_dir = Some(input_dir.clone());
+            }
+            "--format" => {
+                let format = args.next().ok_or("--format needs a value")?;
+                options.output_format = Some(OutputFormat::parse(format)?);
+            }
+            "--timeout" => {
+                options.timeout = Some(Duration::from_secs(parse_u64(arg, args.next())?))
+            }
+            "--threads" => options.threads = Some(parse_threads(parse_u64(arg, args.next())?)?),
+            "--view" => {
+                let view = args.next().ok_or("--view needs a value")?;
+                options.view = Some(view.clone());
+            }
+            "--set" => {
+                let param = args.next().ok_or("--set needs a value")?;
+                let (key, value) = param
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected key=value, got {}", param))?;
+                options
+                    .params
+                    .push((String::from(key), String::from(value)));
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if options.command.is_empty() => options.command = arg.clone(),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    if options.command.is_empty() {
+        return Err(String::from("Missing day"));
+    }
+    Ok(options)
+}
+
+fn selected_parts(config: &Config) -> Vec<u8> {
+    config.default_part.map_or(vec![1, 2], |part| vec![part])
+}
+
+fn explain(solution: &dyn Solution, config: &Config, item: Option<usize>, lines: &[String]) {
+    for part in selected_parts(config) {
+        let mut trace = Trace::new(item);
+        solution.explain(part, lines, &mut trace);
+        if trace.events().is_empty() {
+            println!("{} part {}: nothing to explain", solution.day(), part);
+        } else {
+            println!("{} part {}", solution.day(), part);
+            print!("{}", trace.render());
+        }
+    }
+}
+
+pub fn read_day_input(solution: &dyn Solution, config: &Config) -> io::Result<Vec<String>> {
+    read_input(&config.input_path(solution.day()))
+}
+
+fn print_outcome(day: &str, part: u8, outcome: &Outcome, format: OutputFormat) {
+    let error = match outcome {
+        Outcome::Solved(result) => {
+            match format {
+                OutputFormat::Text => println!("{} {}", day, result),
+                OutputFormat::Json => println!("{}", result.to_json(day)),
+                OutputFormat::Csv => println!(
+                    "{},{},{},{}",
+                    day,
+                    part,
+                    result.answer,
+                    result.elapsed.as_nanos()
+                ),
+            }
+            return;
+        }
+        Outcome::Unsolved => return,
+        Outcome::TimedOut => "timed out",
+        Outcome::Panicked => "panicked",
+        Outcome::Failed(err) => err,
+    };
+    match format {
+        OutputFormat::Text => println!("{} part {}: {}", day, part, error),
+        OutputFormat::Json => println!(
+            "{{\"day\": \"{}\", \"part\": {}, \"error\": {}}}",
+            day,
+            part,
+            json_string(error)
+        ),
+        OutputFormat::Csv => println!("{},{},{},", day, part, escape_csv(error)),
+    }
+}
+
+fn print_csv_header(config: &Config) {
+    if config.output_format == OutputFormat::Csv {
+        println!("day,part,answer,elapsed_ns");
+    }
+}
+
+// Runs each part on the input file as it's read, for --stream.
+fn stream(solution: &'static dyn Solution, config: &Config) {
+    print_csv_header(config);
+    for part in selected_parts(config) {
+        let path = config.input_path(solution.day());
+        let outcome = run_part_streaming_with_timeout(solution, part, path, config.timeout);
+        print_outcome(solution.day(), part, &outcome, config.output_format);
+    }
+}
+
+// Reads the day's input and runs each part on it, printing the answer with how long it took.
+fn run(solution: &'static dyn Solution, config: &Config, options: &Options) {
+    if options.stream && options.explain.is_none() && options.view.is_none() {
+        stream(solution, config);
+        return;
+    }
+    if let Ok(lines) = read_day_input(solution, config) {
+        if let Some(item) = options.explain {
+            explain(solution, config, item, &lines);
+            return;
+        }
+        if let Some(name) = &options.view {
+            let view = View {
+                name,
+                part: config.default_part,
+                format: config.output_format,
+                params: &options.params,
+            };
+            match solution.view(&view, &lines) {
+                Some(Ok(output)) => print!("{}", output),
+                Some(Err(err)) => println!("{}", err),
+                None => println!("{} has no view {}", solution.day(), name),
+            }
+            return;
+        }
+        print_csv_header(config);
+        let lines = Arc::new(lines);
+        for part in selected_parts(config) {
+            let outcome = run_part_with_timeout(solution, part, &lines, config.timeout);
+            print_outcome(solution.day(), part, &outcome, config.output_format);
+        }
+    } else {
+        println!("Failed to open input")
+    }
+}
+
+// Command-line entry point shared by the per-year binaries.
+pub fn main(solutions: &'static [&'static dyn Solution]) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            println!("{}\n{}", err, USAGE);
+            return;
+        }
+    };
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        }
+    };
+    config.override_with(&options);
+    if options.command == "report" {
+        report::run(solutions, &config, &options);
+    } else if let Some(solution) = solutions.iter().find(|s| s.day() == options.command) {
+        run(*solution, &config, &options);
+    } else {
+        println!("Unknown day {}", options.command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::OutputFormat;
+    use crate::runner::{
+        parse_args, run_part, run_part_streaming, run_part_streaming_with_timeout,
+        run_part_with_timeout, run_parts, Options, Outcome, PartResult, Solution, View,
+    };
+    use std::fs;
+    use std::io::{self, BufRead};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    struct CountLines;
+
+    impl Solution for CountLines {
+        fn day(&self) -> &'static str {
+            "count"
+        }
+
+        fn part1(&self, lines: &[String]) -> Option<i64> {
+            Some(lines.len() as i64)
+        }
+    }
+
+    #[test]
+    fn run_part_success() {
+        let lines = vec![String::from("a"), String::from("b")];
+        let result = run_part(&CountLines, 1, &lines).unwrap().unwrap();
+        assert_eq!(result.part, 1);
+        assert_eq!(result.answer, 2);
+        assert_eq!(run_part(&CountLines, 2, &lines), None);
+    }
+
+    #[test]
+    fn run_parts_skips_unsolved() {
+        let lines = vec![String::from("a")];
+        let results = run_parts(&CountLines, &lines);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].as_ref().unwrap().answer, 1);
+    }
+
+    struct BadInput;
+
+    impl Solution for BadInput {
+        fn day(&self) -> &'static str {
+            "bad"
+        }
+
+        fn try_part1(&self, _lines: &[String]) -> Option<Result<i64, String>> {
+            Some(Err(String::from("line 1: no digit")))
+        }
+    }
+
+    #[test]
+    fn run_part_failed() {
+        let lines = Arc::new(vec![String::from("a")]);
+        assert_eq!(
+            run_part(&BadInput, 1, &lines),
+            Some(Err(String::from("line 1: no digit")))
+        );
+        assert_eq!(
+            run_part_with_timeout(&BadInput, 1, &lines, None),
+            Outcome::Failed(String::from("line 1: no digit"))
+        );
+    }
+
+    struct Spin;
+
+    impl Solution for Spin {
+        fn day(&self) -> &'static str {
+            "spin"
+        }
+
+        fn part1(&self, _lines: &[String]) -> Option<i64> {
+            std::thread::sleep(Duration::from_secs(5));
+            Some(0)
+        }
+    }
+
+    struct Panics;
+
+    impl Solution for Panics {
+        fn day(&self) -> &'static str {
+            "panics"
+        }
+
+        fn part1(&self, _lines: &[String]) -> Option<i64> {
+            panic!("bad input")
+        }
+    }
+
+    #[test]
+    fn run_part_with_timeout_panicked() {
+        let lines = Arc::new(vec![String::from("a")]);
+        assert_eq!(
+            run_part_with_timeout(&Panics, 1, &lines, Some(Duration::from_secs(5))),
+            Outcome::Panicked
+        );
+        assert_eq!(
+            run_part_with_timeout(&Panics, 1, &lines, None),
+            Outcome::Panicked
+        );
+    }
+
+    #[test]
+    fn run_part_with_timeout_success() {
+        let lines = Arc::new(vec![String::from("a")]);
+        assert!(matches!(
+            run_part_with_timeout(&CountLines, 1, &lines, Some(Duration::from_secs(5))),
+            Outcome::Solved(PartResult { answer: 1, .. })
+        ));
+        assert_eq!(
+            run_part_with_timeout(&CountLines, 2, &lines, None),
+            Outcome::Unsolved
+        );
+        assert_eq!(
+            run_part_with_timeout(&Spin, 1, &lines, Some(Duration::from_millis(10))),
+            Outcome::TimedOut
+        );
+    }
+
+    struct StreamLines;
+
+    impl Solution for StreamLines {
+        fn day(&self) -> &'static str {
+            "stream"
+        }
+
+        fn solve_stream(&self, part: u8, reader: &mut dyn BufRead) -> Option<io::Result<i64>> {
+            (part == 1).then(|| {
+                reader
+                    .lines()
+                    .try_fold(0, |count, line| line.map(|_| count + 1))
+            })
+        }
+    }
+
+    #[test]
+    fn run_part_streaming_success() {
+        let path = std::env::temp_dir().join(format!("aoc-stream-{}", std::process::id()));
+        fs::write(&path, "a\nb\nc\n").unwrap();
+        let result = run_part_streaming(&StreamLines, 1, &path).unwrap().unwrap();
+        assert_eq!((result.part, result.answer), (1, 3));
+        assert!(run_part_streaming(&StreamLines, 2, &path).is_none());
+        assert!(run_part_streaming(&CountLines, 1, &path).is_none());
+        assert!(matches!(
+            run_part_streaming_with_timeout(&StreamLines, 1, path.clone(), None),
+            Outcome::Solved(PartResult { answer: 3, .. })
+        ));
+        assert_eq!(
+            run_part_streaming_with_timeout(&CountLines, 1, path.clone(), None),
+            Outcome::Failed(String::from("can't stream"))
+        );
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            run_part_streaming(&StreamLines, 1, &path),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn part_result_json() {
+        let result = PartResult {
+            part: 1,
+            answer: 42,
+            elapsed: Duration::from_nanos(1500),
+            alloc: None,
+        };
+        assert_eq!(
+            result.to_json("day01"),
+            "{\"day\": \"day01\", \"part\": 1, \"answer\": 42, \"elapsed_ns\": 1500, \"peak_bytes\": null}"
+        );
+    }
+
+    #[test]
+    fn part_result_display() {
+        let result = PartResult {
+            part: 2,
+            answer: 42,
+            elapsed: Duration::from_millis(3),
+            alloc: None,
+        };
+        assert_eq!(result.to_string(), "part 2: 42 (3ms)");
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|&arg| String::from(arg)).collect()
+    }
+
+    #[test]
+    fn parse_args_day() {
+        assert_eq!(
+            parse_args(&args(&["day01"])),
+            Ok(Options {
+                command: String::from("day01"),
+                ..Options::default()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_args_part_and_explain() {
+        assert_eq!(
+            parse_args(&args(&["day05", "--part", "1", "--explain", "3"])),
+            Ok(Options {
+                command: String::from("day05"),
+                part: Some(1),
+                explain: Some(Some(3)),
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["day05", "--explain", "--part", "2"])),
+            Ok(Options {
+                command: String::from("day05"),
+                part: Some(2),
+                explain: Some(None),
+                ..Options::default()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_args_stream() {
+        assert_eq!(
+            parse_args(&args(&["day03", "--stream", "--part", "2"])),
+            Ok(Options {
+                command: String::from("day03"),
+                part: Some(2),
+                stream: true,
+                ..Options::default()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_args_report() {
+        assert_eq!(
+            parse_args(&args(&["report", "--redact", "--out", "docs"])),
+            Ok(Options {
+                command: String::from("report"),
+                redact: true,
+                out_dir: Some(String::from("docs")),
+                ..Options::default()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_args_config_overrides() {
+        assert_eq!(
+            parse_args(&args(&[
+                "day01",
+                "--input-dir",
+                "inputs",
+                "--format",
+                "json",
+                "--timeout",
+                "10",
+                "--threads",
+                "4",
+            ])),
+            Ok(Options {
+                command: String::from("day01"),
+                input_dir: Some(String::from("inputs")),
+                output_format: Some(OutputFormat::Json),
+                timeout: Some(Duration::from_secs(10)),
+                threads: Some(4),
+                ..Options::default()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_args_view() {
+        assert_eq!(
+            parse_args(&args(&[
+                "day02",
+                "--view",
+                "games",
+                "--set",
+                "red=12",
+                "--set",
+                "where=a=b"
+            ])),
+            Ok(Options {
+                command: String::from("day02"),
+                view: Some(String::from("games")),
+                params: vec![
+                    (String::from("red"), String::from("12")),
+                    (String::from("where"), String::from("a=b")),
+                ],
+                ..Options::default()
+            })
+        );
+    }
+
+    #[test]
+    fn view_param() {
+        let params = vec![
+            (String::from("red"), String::from("12")),
+            (String::from("red"), String::from("13")),
+        ];
+        let view = View {
+            name: "games",
+            part: None,
+            format: OutputFormat::Text,
+            params: &params,
+        };
+        assert_eq!(view.param("red"), Some("13"));
+        assert_eq!(view.param("blue"), None);
+    }
+
+    #[test]
+    fn parse_args_errors() {
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["day01", "--part", "3"])).is_err());
+        assert!(parse_args(&args(&["day01", "--part"])).is_err());
+        assert!(parse_args(&args(&["day01", "--bogus"])).is_err());
+        assert!(parse_args(&args(&["day01", "day02"])).is_err());
+        assert!(parse_args(&args(&["report", "--out"])).is_err());
+        assert!(parse_args(&args(&["day01", "--timeout", "soon"])).is_err());
+        assert!(parse_args(&args(&["day01", "--threads", "0"])).is_err());
+        assert!(parse_args(&args(&["day01", "--format", "xml"])).is_err());
+        assert!(parse_args(&args(&["day01", "--view"])).is_err());
+        assert!(parse_args(&args(&["day01", "--set", "red"])).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+// One step of a solution's reasoning, attached to the input item (usually a line) it's about.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TraceEvent {
+    pub item: usize,
+    pub label: &'static str,
+    pub detail: String,
+}
+
+// Collects trace events from a solution's `explain`, optionally keeping only a single item.
+#[derive(Debug, Default)]
+pub struct Trace {
+    item: Option<usize>,
+    events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    // Traces every item when `item` is None.
+    pub fn new(item: Option<usize>) -> Trace {
+        Trace {
+            item,
+            events: Vec::new(),
+        }
+    }
+
+    // Whether events for this item are kept. Lets solutions skip work for items nobody asked about.
+    pub fn wants(&self, item: usize) -> bool {
+        self.item.is_none_or(|wanted| wanted == item)
+    }
+
+    pub fn emit(&mut self, item: usize, label: &'static str, detail: String) {
+        if self.wants(item) {
+            self.events.push(TraceEvent {
+                item,
+                label,
+                detail,
+            });
+        }
+    }
+
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    // Each event as (label, detail), for comparing against expected output.
+    pub fn pairs(&self) -> Vec<(&str, &str)> {
+        self.events
+            .iter()
+            .map(|event| (event.label, event.detail.as_str()))
+            .collect()
+    }
+
+    // Groups the events by item, with the labels lined up. Events for the same item stay in the
+    // order they were emitted.
+    pub fn render(&self) -> String {
+        let mut events: Vec<&TraceEvent> = self.events.iter().collect();
+        events.sort_by_key(|event| event.item);
+        let label_width = events
+            .iter()
+            .map(|event| event.label.len())
+            .max()
+            .unwrap_or(0);
+        let mut out = String::new();
+        let mut prev_item = None;
+        for event in events {
+            if prev_item != Some(event.item) {
+                out += &format!("item {}\n", event.item);
+                prev_item = Some(event.item);
+            }
+            out += &format!(
+                "  {:width$}  {}\n",
+                event.label,
+                event.detail,
+                width = label_width
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::{Trace, TraceEvent};
+
+    #[test]
+    fn emit_filters_items() {
+        let mut trace = Trace::new(Some(1));
+        trace.emit(0, "seed", String::from("79"));
+        trace.emit(1, "seed", String::from("14"));
+        assert!(!trace.wants(0));
+        assert!(trace.wants(1));
+        assert_eq!(
+            trace.events(),
+            &[TraceEvent {
+                item: 1,
+                label: "seed",
+                detail: String::from("14"),
+            }]
+        );
+    }
+
+    #[test]
+    fn emit_all_items() {
+        let mut trace = Trace::new(None);
+        trace.emit(0, "seed", String::from("79"));
+        trace.emit(1, "seed", String::from("14"));
+        assert_eq!(trace.events().len(), 2);
+    }
+
+    #[test]
+    fn pairs_success() {
+        let mut trace = Trace::new(None);
+        trace.emit(0, "seed", String::from("79"));
+        trace.emit(0, "location", String::from("82"));
+        assert_eq!(trace.pairs(), vec![("seed", "79"), ("location", "82")]);
+    }
+
+    #[test]
+    fn render_groups_by_item() {
+        let mut trace = Trace::new(None);
+        trace.emit(0, "seed", String::from("79"));
+        trace.emit(1, "seed", String::from("14"));
+        trace.emit(0, "location", String::from("82"));
+        assert_eq!(
+            trace.render(),
+            "item 0\n  seed      79\n  location  82\nitem 1\n  seed      14\n"
+        );
+    }
+}